@@ -1,14 +1,91 @@
-use std::error::Error;
+use std::collections::hash_map::Entry;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+#[cfg(feature = "compression")]
+use std::io::Write;
+use std::panic::AssertUnwindSafe;
+use std::pin::pin;
+#[cfg(feature = "compression")]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use reqwest::header::{HeaderMap, HeaderValue};
+use futures_util::future::{self, Either, FutureExt};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, SeqAccess, Visitor};
+use tokio::sync::{Mutex, OnceCell};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
 
+use super::compare_versions;
+use super::error::ApiError;
 use super::executor::RawExecResponse;
 use super::ExecResponse;
-use super::ExecResult;
 use super::Executor;
+use super::PistonError;
 use super::Runtime;
 
+/// The url of the public Piston instance.
+const DEFAULT_URL: &str = "https://emkc.org/api/v2/piston";
+
+/// The header carrying the request ID, when idempotency is enabled.
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// The number of times read operations are retried by default.
+const DEFAULT_READ_RETRIES: u32 = 2;
+
+/// The delay before the first retry, which doubles for each retry after.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// A summary of the results of [`Client::execute_batch`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BatchSummary {
+    /// The number of executions Piston responded to successfully.
+    pub succeeded: usize,
+    /// The number of executions that failed.
+    pub failed: usize,
+}
+
+impl BatchSummary {
+    /// Summarizes the results of a batch execution.
+    ///
+    /// # Arguments
+    /// - `results` - The results to summarize.
+    ///
+    /// # Returns
+    /// - [`BatchSummary`] - The new summary.
+    ///
+    /// # Example
+    /// ```
+    /// let results = vec![Err(piston_rs::PistonError::Cancelled)];
+    /// let summary = piston_rs::BatchSummary::from_results(&results);
+    ///
+    /// assert_eq!(summary.succeeded, 0);
+    /// assert_eq!(summary.failed, 1);
+    /// ```
+    pub fn from_results(results: &[Result<ExecResponse, PistonError>]) -> Self {
+        let succeeded = results.iter().filter(|r| r.is_ok()).count();
+
+        Self {
+            succeeded,
+            failed: results.len() - succeeded,
+        }
+    }
+}
+
 /// A client used to send requests to Piston.
+///
+/// Cloning a client is cheap, and clones share the same underlying
+/// connection pool. A client is also [`Send`] and [`Sync`], so it can
+/// be shared between threads, for example in an [`Arc`].
+///
+/// Requests are sent using [`reqwest`], so they must be awaited from
+/// within a [tokio](https://tokio.rs) runtime.
 #[derive(Debug, Clone)]
 pub struct Client {
     /// The base url for Piston.
@@ -17,6 +94,143 @@ pub struct Client {
     client: reqwest::Client,
     /// The headers to send with each request.
     headers: HeaderMap,
+    /// The configuration used to build the reqwest client.
+    config: HttpConfig,
+    /// The limiter spacing out requests, if rate limited.
+    limiter: Option<RateLimiter>,
+    /// Whether or not to send a request ID with each execution.
+    idempotency: bool,
+    /// The number of times to retry fetching runtimes.
+    read_retries: u32,
+    /// The number of times to retry executions.
+    write_retries: u32,
+    /// The maximum size of `stdin` in bytes, if limited.
+    max_stdin: Option<usize>,
+    /// The version to execute with when an executor uses `"*"`, if any.
+    default_version: Option<String>,
+    /// How long fetched runtimes are reused without contacting Piston,
+    /// if cached.
+    runtime_cache_ttl: Option<Duration>,
+    /// The last runtimes fetched from Piston, and their `ETag`.
+    runtimes_cache: Arc<StdMutex<Option<CachedRuntimes>>>,
+    /// The executions in flight, if identical executions are coalesced.
+    in_flight: Option<InFlight>,
+    /// Whether or not Piston is still believed to accept compressed
+    /// request bodies, if compression is enabled.
+    #[cfg(feature = "compression")]
+    compression: Option<Arc<AtomicBool>>,
+}
+
+/// Executions currently awaiting a response from Piston, keyed by
+/// [`Executor::content_hash`]. The serialized request body is kept
+/// alongside each execution, so executions whose hashes collide never
+/// share a response.
+type InFlight = Arc<StdMutex<HashMap<u64, (String, Arc<OnceCell<ExecResponse>>)>>>;
+
+/// Runtimes fetched from Piston, kept to answer a `304 Not Modified`
+/// or to be reused until the cache time-to-live expires.
+#[derive(Clone, Debug)]
+struct CachedRuntimes {
+    /// The `ETag` Piston sent with the runtimes, if any.
+    etag: Option<HeaderValue>,
+    /// When the runtimes were last fetched or revalidated.
+    fetched_at: Instant,
+    /// The runtimes.
+    runtimes: Vec<Runtime>,
+}
+
+/// Spaces out requests so no more than a set number are sent each
+/// second. Clones share the same limit.
+#[derive(Clone, Debug)]
+struct RateLimiter {
+    /// The time between requests.
+    period: Duration,
+    /// The interval that ticks once per request. This is created on
+    /// first use, as it must be created within a tokio runtime.
+    interval: Arc<Mutex<Option<Interval>>>,
+}
+
+impl RateLimiter {
+    /// Creates a new limiter. Rates above one request per nanosecond
+    /// are limited to one request per nanosecond.
+    ///
+    /// # Arguments
+    /// - `per_second` - The maximum requests per second.
+    ///
+    /// # Returns
+    /// - [`RateLimiter`] - The new RateLimiter.
+    fn per_second(per_second: u32) -> Self {
+        Self {
+            period: (Duration::from_secs(1) / per_second).max(Duration::from_nanos(1)),
+            interval: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Waits until another request may be sent.
+    async fn acquire(&self) {
+        let mut interval = self.interval.lock().await;
+        let interval = interval.get_or_insert_with(|| {
+            let mut interval = tokio::time::interval(self.period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+
+        interval.tick().await;
+    }
+}
+
+/// The configuration used to build the underlying reqwest client.
+#[derive(Clone, Debug, Default)]
+struct HttpConfig {
+    /// The maximum idle connections to keep per host.
+    pool_max_idle_per_host: Option<usize>,
+    /// How long to keep idle connections alive.
+    pool_idle_timeout: Option<Duration>,
+    /// The timeout for each request, if any.
+    timeout: Option<Duration>,
+    /// The proxy to route requests through, if any.
+    proxy: Option<reqwest::Proxy>,
+    /// The reqwest client to use instead of building one, if any.
+    client: Option<reqwest::Client>,
+}
+
+impl HttpConfig {
+    /// Builds a reqwest client from this configuration. If a reqwest
+    /// client was provided, it is used as is instead.
+    ///
+    /// # Returns
+    /// - [`reqwest::Result<reqwest::Client>`] - The new client, or the
+    ///   error if it could not be built.
+    fn build(&self) -> reqwest::Result<reqwest::Client> {
+        if let Some(client) = &self.client {
+            return Ok(client.clone());
+        }
+
+        let mut builder = reqwest::Client::builder();
+
+        #[cfg(feature = "rustls-tls")]
+        {
+            builder = builder.use_rustls_tls();
+        }
+
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+
+        builder.build()
+    }
 }
 
 impl Default for Client {
@@ -54,11 +268,7 @@ impl Client {
     /// assert!(!client.get_headers().contains_key("Authorization"));
     /// ```
     pub fn new() -> Self {
-        Self {
-            url: "https://emkc.org/api/v2/piston".to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(None),
-        }
+        Self::from_parts(DEFAULT_URL, None).expect("Failed to build the client")
     }
 
     /// Creates a new Client with a url that runs the piston code execution engine.
@@ -77,11 +287,7 @@ impl Client {
     /// assert_eq!(client.get_url(), "http://localhost:3000");
     /// ```
     pub fn with_url(url: &str) -> Self {
-        Self {
-            url: url.to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(None),
-        }
+        Self::from_parts(url, None).expect("Failed to build the client")
     }
 
     /// Creates a new client, with an api key.
@@ -90,21 +296,20 @@ impl Client {
     /// - `key` - The api key to use.
     ///
     /// # Returns
-    /// - [`Client`] - The new Client.
+    /// - [`Result<Client, PistonError>`] - The new Client, or the error
+    ///   if the key is not a valid header value.
     ///
     /// # Example
     /// ```
-    /// let client = piston_rs::Client::with_key("123abc");
+    /// let client = piston_rs::Client::with_key("123abc").unwrap();
     ///
     /// assert!(client.get_headers().contains_key("Authorization"));
     /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    ///
+    /// assert!(piston_rs::Client::with_key("bad\nkey").is_err());
     /// ```
-    pub fn with_key(key: &str) -> Self {
-        Self {
-            url: "https://emkc.org/api/v2/piston".to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(Some(key)),
-        }
+    pub fn with_key(key: &str) -> Result<Self, PistonError> {
+        Self::from_parts(DEFAULT_URL, Some(key))
     }
 
     /// Creates a new Client using a url and an api key.
@@ -114,225 +319,3389 @@ impl Client {
     /// - `key` - The api key to use.
     ///
     /// # Returns
-    /// - [`Client`] - The new Client.
+    /// - [`Result<Client, PistonError>`] - The new Client, or the error
+    ///   if the key is not a valid header value.
     ///
     /// # Example
     /// ```
-    /// let client = piston_rs::Client::with_url_and_key("http://localhost:3000", "123abc");
+    /// let client = piston_rs::Client::with_url_and_key("http://localhost:3000", "123abc").unwrap();
     /// assert_eq!(client.get_url(), "http://localhost:3000");
     /// assert!(client.get_headers().contains_key("Authorization"));
     /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
     /// ```
-    pub fn with_url_and_key(url: &str, key: &str) -> Self {
-        Self {
-            url: url.to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(Some(key)),
-        }
+    pub fn with_url_and_key(url: &str, key: &str) -> Result<Self, PistonError> {
+        Self::from_parts(url, Some(key))
     }
 
-    /// The base url for the Piston V2 API that is being used by this client.
+    /// Creates a new client from its parts, using the default http
+    /// configuration.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use as the underlying piston backend.
+    /// - `key` - The api key to use, if any.
     ///
     /// # Returns
+    /// - [`Result<Client, PistonError>`] - The new Client, or the error
+    ///   if the key is not a valid header value, or the TLS backend can
+    ///   not be initialized.
+    fn from_parts(url: &str, key: Option<&str>) -> Result<Self, PistonError> {
+        let mut builder = Self::builder().set_url(url);
+        if let Some(k) = key {
+            builder = builder.set_key(k);
+        }
+
+        builder.build()
+    }
+
+    /// Creates a new client builder, for configuring several options
+    /// at once.
     ///
-    /// - [`String`] - The requested url.
+    /// # Returns
+    /// - [`ClientBuilder`] - The new ClientBuilder.
     ///
     /// # Example
     /// ```
-    /// let client = piston_rs::Client::new();
+    /// let client = piston_rs::Client::builder()
+    ///     .set_url("http://localhost:3000")
+    ///     .set_key("123abc")
+    ///     .build()
+    ///     .unwrap();
     ///
-    /// assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
     /// ```
-    pub fn get_url(&self) -> String {
-        self.url.clone()
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
     }
 
-    /// The headers being used by this client.
+    /// Sets the maximum number of idle connections the client keeps
+    /// open per host.
+    ///
+    /// # Arguments
+    /// - `max` - The maximum idle connections.
     ///
     /// # Returns
+    /// - [`Self`] - For chained method calls.
     ///
-    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::with_url("http://localhost:3000")
+    ///     .with_pool_max_idle_per_host(16);
+    /// ```
+    #[must_use]
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool_max_idle_per_host = Some(max);
+        self.rebuild_client()
+    }
+
+    /// Sets how long the client keeps idle connections open.
+    ///
+    /// # Arguments
+    /// - `timeout` - The idle timeout.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
-    /// let client = piston_rs::Client::new();
-    /// let headers = client.get_headers();
+    /// use std::time::Duration;
     ///
-    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// let client = piston_rs::Client::with_url("http://localhost:3000")
+    ///     .with_pool_idle_timeout(Duration::from_secs(90));
     /// ```
-    pub fn get_headers(&self) -> HeaderMap {
-        self.headers.clone()
+    #[must_use]
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool_idle_timeout = Some(timeout);
+        self.rebuild_client()
     }
 
-    /// Generates the headers the client should use.
+    /// Routes all requests through the given http proxy.
     ///
-    /// # Returns
+    /// # Arguments
+    /// - `proxy_url` - The url of the proxy.
     ///
-    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    /// # Returns
+    /// - [`Result<Self, PistonError>`] - The client, or the error if
+    ///   the proxy url is not valid.
     ///
     /// # Example
-    /// ```ignore # Fails to compile (private function)
-    /// let headers = piston_rs::Client::generate_headers(None);
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_proxy("http://proxy.internal:8080");
     ///
-    /// assert!(!headers.contains_key("Authorization"));
-    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
-    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// assert!(client.is_ok());
+    /// assert!(piston_rs::Client::new().with_proxy("not a url").is_err());
+    /// ```
+    pub fn with_proxy(mut self, proxy_url: &str) -> Result<Self, PistonError> {
+        self.config.proxy = Some(reqwest::Proxy::all(proxy_url)?);
+        self.client = self.config.build()?;
+        Ok(self)
+    }
+
+    /// Sends all requests using the given reqwest client, such as one
+    /// configured with custom default headers, TLS settings, or
+    /// connectors. The url and headers of this client are kept.
     ///
-    /// let headers = piston_rs::Client::generate_headers(Some("123abc"));
+    /// The pool, timeout, and proxy options of this client are not
+    /// applied to the given client, whether they are set before or
+    /// after it, and the given client is kept when they are set.
     ///
-    /// assert_eq!(headers.get("Authorization").unwrap(), "123abc");
-    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
-    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// # Arguments
+    /// - `client` - The reqwest client to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
     /// ```
-    fn generate_headers(key: Option<&str>) -> HeaderMap {
-        let mut headers = HeaderMap::with_capacity(3);
-        headers.insert("Accept", HeaderValue::from_str("application/json").unwrap());
-        headers.insert("User-Agent", HeaderValue::from_str("piston-rs").unwrap());
-
-        if let Some(k) = key {
-            headers.insert("Authorization", HeaderValue::from_str(k).unwrap());
-        };
-
-        headers
+    /// let reqwest_client = reqwest::Client::builder()
+    ///     .user_agent("my-app/1.0")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let client = piston_rs::Client::with_url("http://localhost:3000")
+    ///     .with_reqwest_client(reqwest_client);
+    /// ```
+    #[must_use]
+    pub fn with_reqwest_client(mut self, client: reqwest::Client) -> Self {
+        self.config.client = Some(client);
+        self.rebuild_client()
     }
 
-    /// Fetches the runtimes from Piston. **This is an http request**.
+    /// Limits the number of requests the client sends each second,
+    /// spacing out bursts such as [`Client::execute_batch`]. Clones of
+    /// the client share the same limit. By default, requests are not
+    /// limited.
+    ///
+    /// # Arguments
+    /// - `per_second` - The maximum requests per second. `0` removes
+    ///   the limit.
     ///
     /// # Returns
-    /// - [`Result<Vec<Runtime>, Box<dyn Error>>`] - The available
-    /// runtimes or the error, if any.
+    /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```no_run
     /// # #[tokio::test]
-    /// # async fn test_fetch_runtimes() {
-    /// let client = piston_rs::Client::new();
+    /// # async fn test_with_rate_limit() {
+    /// let client = piston_rs::Client::new()
+    ///     .with_rate_limit(5);
     ///
-    /// if let Ok(runtimes) = client.fetch_runtimes().await {
-    ///     assert!(!runtimes.is_empty());
-    /// } else {
-    ///     // There was an error contacting Piston.
-    /// }
+    /// let executors = vec![
+    ///     piston_rs::Executor::new()
+    ///         .set_language("python")
+    ///         .add_file(piston_rs::File::default().set_content("print(1)"));
+    ///     10
+    /// ];
+    ///
+    /// // Sent over roughly two seconds, rather than all at once.
+    /// let results = client.execute_batch(&executors).await;
     /// # }
     /// ```
-    pub async fn fetch_runtimes(&self) -> Result<Vec<Runtime>, Box<dyn Error>> {
-        let endpoint = format!("{}/runtimes", self.url);
-        let runtimes = self
-            .client
-            .get(endpoint)
-            .headers(self.headers.clone())
-            .send()
-            .await?
-            .json::<Vec<Runtime>>()
-            .await?;
+    #[must_use]
+    pub fn with_rate_limit(mut self, per_second: u32) -> Self {
+        self.limiter = match per_second {
+            0 => None,
+            n => Some(RateLimiter::per_second(n)),
+        };
 
-        Ok(runtimes)
+        self
     }
 
-    /// Executes code using a given executor. **This is an http
-    /// request**.
+    /// Sends a unique `X-Request-Id` header with each execution. The
+    /// ID is generated once per call to an execute method, and reused
+    /// if the request is retried, so instances that support
+    /// idempotency keys can avoid running the same code twice.
+    /// **Deduplication depends on server support**, the public Piston
+    /// instance ignores the header. Disabled by default.
     ///
     /// # Arguments
-    /// - `executor` - The executor to use.
+    /// - `enabled` - Whether or not to send request IDs.
     ///
     /// # Returns
-    /// - [`Result<ExecutorResponse, Box<dyn Error>>`] - The response
-    /// from Piston or the error, if any.
+    /// - [`Self`] - For chained method calls.
     ///
     /// # Example
-    /// ```no_run
-    /// # #[tokio::test]
-    /// # async fn test_execute() {
-    /// let client = piston_rs::Client::new();
-    /// let executor = piston_rs::Executor::new()
-    ///     .set_language("rust")
-    ///     .set_version("1.50.0")
-    ///     .add_file(piston_rs::File::default().set_content(
-    ///         "fn main() { println!(\"42\"); }",
-    ///     ));
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_idempotency(true);
     ///
-    /// if let Ok(response) = client.execute(&executor).await {
-    ///     assert!(response.compile.is_some());
-    ///     assert!(response.run.is_ok());
-    ///     assert!(response.is_ok());
-    /// } else {
-    ///     // There was an error contacting Piston.
-    /// }
-    /// # }
+    /// assert!(!client.get_headers().contains_key("X-Request-Id"));
     /// ```
-    pub async fn execute(&self, executor: &Executor) -> Result<ExecResponse, Box<dyn Error>> {
-        let endpoint = format!("{}/execute", self.url);
-
-        match self
-            .client
-            .post(endpoint)
-            .headers(self.headers.clone())
-            .json::<Executor>(executor)
-            .send()
-            .await
-        {
-            Ok(data) => {
-                let status = data.status();
+    #[must_use]
+    pub fn with_idempotency(mut self, enabled: bool) -> Self {
+        self.idempotency = enabled;
+        self
+    }
 
-                match status {
-                    reqwest::StatusCode::OK => {
-                        let response = data.json::<RawExecResponse>().await?;
+    /// Sets the number of times fetching runtimes is retried after a
+    /// transient failure: a connection error, a timeout, or a `429`,
+    /// `502`, `503`, or `504` status. Each retry waits twice as long as
+    /// the last, starting at 100 milliseconds.
+    ///
+    /// Fetching runtimes has no side effects, so it is safe to retry,
+    /// and is retried twice by default.
+    ///
+    /// # Arguments
+    /// - `retries` - The number of retries, or `0` to never retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_read_retries(5);
+    /// ```
+    #[must_use]
+    pub fn with_read_retries(mut self, retries: u32) -> Self {
+        self.read_retries = retries;
+        self
+    }
 
-                        Ok(ExecResponse {
-                            language: response.language,
-                            version: response.version,
-                            run: response.run,
-                            compile: response.compile,
-                            status: status.as_u16(),
-                        })
-                    }
-                    _ => {
-                        let text = format!("{}: {}", data.status(), data.text().await?);
-
-                        let exec_result = ExecResult {
-                            stdout: String::new(),
-                            stderr: text.clone(),
-                            output: text,
-                            code: Some(1),
-                            signal: None,
-                        };
-
-                        let exec_response = ExecResponse {
-                            language: executor.language.clone(),
-                            version: executor.version.clone(),
-                            run: exec_result,
-                            compile: None,
-                            status: status.as_u16(),
-                        };
+    /// Sets the number of times executions are retried after a
+    /// transient failure, like [`Client::with_read_retries`].
+    ///
+    /// Executions are **not** retried by default, as a request that
+    /// failed on the way back may have already run the code, and
+    /// running it again could repeat its side effects. Consider
+    /// [`Client::with_idempotency`] when enabling this.
+    ///
+    /// # Arguments
+    /// - `retries` - The number of retries, or `0` to never retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_idempotency(true)
+    ///     .with_write_retries(2);
+    /// ```
+    #[must_use]
+    pub fn with_write_retries(mut self, retries: u32) -> Self {
+        self.write_retries = retries;
+        self
+    }
 
-                        Ok(exec_response)
-                    }
-                }
-            }
-            Err(e) => Err(Box::new(e)),
-        }
+    /// Compresses execute request bodies with gzip, sending a
+    /// `Content-Encoding: gzip` header, which helps large submissions
+    /// over slow links. If Piston responds with `415 Unsupported Media
+    /// Type`, the request is sent again uncompressed, and later
+    /// requests are no longer compressed. Clones of the client share
+    /// this. Requires the `compression` feature.
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to compress request bodies.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_request_compression(true);
+    /// ```
+    #[cfg(feature = "compression")]
+    #[must_use]
+    pub fn with_request_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled.then(|| Arc::new(AtomicBool::new(true)));
+        self
     }
-}
 
-#[cfg(test)]
-mod test_client_private {
-    use super::Client;
+    /// Shares a single request to Piston between identical executions
+    /// that are in flight at the same time, such as many users running
+    /// the same example. Executors are identical if their
+    /// [`Executor::content_hash`] is equal. Clones of the client share
+    /// the same in flight executions. By default, executions are not
+    /// coalesced.
+    ///
+    /// Only successful responses are shared. If the request fails,
+    /// each waiting execution sends its own request in turn.
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to coalesce executions.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_request_coalescing(true);
+    /// ```
+    #[must_use]
+    pub fn with_request_coalescing(mut self, enabled: bool) -> Self {
+        self.in_flight = match (enabled, self.in_flight) {
+            (true, None) => Some(InFlight::default()),
+            (true, in_flight) => in_flight,
+            (false, _) => None,
+        };
 
-    #[test]
-    fn test_gen_headers_no_key() {
-        let headers = Client::generate_headers(None);
+        self
+    }
 
-        assert!(!headers.contains_key("Authorization"));
-        assert_eq!(headers.get("Accept").unwrap(), "application/json");
-        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// Rejects executions whose `stdin` is larger than the given size,
+    /// with [`PistonError::InvalidExecutor`], before they are sent. By
+    /// default, `stdin` is not limited.
+    ///
+    /// # Arguments
+    /// - `bytes` - The maximum size of `stdin` in bytes.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_with_max_stdin() {
+    /// let client = piston_rs::Client::new()
+    ///     .with_max_stdin(4);
+    ///
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .set_stdin("too long")
+    ///     .add_file(piston_rs::File::default().set_content("print(input())"));
+    ///
+    /// match client.execute(&executor).await {
+    ///     Err(piston_rs::PistonError::InvalidExecutor(details)) => println!("{}", details),
+    ///     other => panic!("Unexpected result: {:?}", other),
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_max_stdin(mut self, bytes: usize) -> Self {
+        self.max_stdin = Some(bytes);
+        self
+    }
+
+    /// Executes with the given version whenever an executor's version
+    /// is `"*"`, the default. Executors with a version set explicitly
+    /// are executed with it unchanged.
+    ///
+    /// # Arguments
+    /// - `version` - The version to execute with by default.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new()
+    ///     .with_default_version("3.10.0");
+    /// ```
+    #[must_use]
+    pub fn with_default_version(mut self, version: &str) -> Self {
+        self.default_version = Some(version.to_string());
+        self
+    }
+
+    /// Reuses runtimes fetched by [`Client::fetch_runtimes`] for the
+    /// given duration, without contacting Piston. Once it expires, the
+    /// next call fetches them again. By default, runtimes are always
+    /// fetched, and only revalidated using their `ETag`.
+    ///
+    /// # Arguments
+    /// - `ttl` - How long fetched runtimes are reused.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache_ttl(Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn with_runtime_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.runtime_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Generates a new request ID, unique within this process and
+    /// unlikely to collide across processes.
+    ///
+    /// # Returns
+    /// - [`String`] - The request ID, as 32 hex characters.
+    fn generate_request_id() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u128(nanos);
+        let high = hasher.finish();
+
+        hasher.write_u8(0);
+        let low = hasher.finish();
+
+        format!("{:016x}{:016x}", high, low)
+    }
+
+    /// Waits until the rate limit, if any, allows another request.
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Makes an attempt, retrying it after transient failures.
+    ///
+    /// # Arguments
+    /// - `retries` - The maximum number of retries.
+    /// - `attempt` - Makes a single attempt.
+    ///
+    /// # Returns
+    /// - [`Result<T, PistonError>`] - The result of the last attempt.
+    async fn retry<T, F, Fut>(retries: u32, mut attempt: F) -> Result<T, PistonError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, PistonError>>,
+    {
+        let mut delay = RETRY_DELAY;
+
+        for _ in 0..retries {
+            match attempt().await {
+                Err(e) if Self::is_transient(&e) => {
+                    debug!("Retrying in {:?} after {}", delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+
+        attempt().await
+    }
+
+    /// Whether or not an error is likely to succeed if retried.
+    ///
+    /// # Arguments
+    /// - `error` - The error.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the error is transient.
+    fn is_transient(error: &PistonError) -> bool {
+        match error {
+            PistonError::Http(e) => e.is_connect() || e.is_timeout(),
+            PistonError::Api { status, .. } => matches!(status, 429 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+
+    /// Sends a request for the runtimes, retrying transient failures.
+    ///
+    /// # Arguments
+    /// - `endpoint` - The runtimes endpoint.
+    /// - `headers` - The headers to send with the request.
+    ///
+    /// # Returns
+    /// - [`Result<reqwest::Response, PistonError>`] - The successful,
+    ///   or not modified, response from Piston or the error, if any.
+    async fn get_runtimes(
+        &self,
+        endpoint: &str,
+        headers: &HeaderMap,
+    ) -> Result<reqwest::Response, PistonError> {
+        Self::retry(self.read_retries, move || async move {
+            self.throttle().await;
+            debug!("Fetching runtimes from {}", endpoint);
+
+            let response = self
+                .client
+                .get(endpoint)
+                .headers(headers.clone())
+                .send()
+                .await?;
+
+            let status = response.status();
+            debug!("Received status {} from Piston", status);
+            record_status!(status);
+
+            if status.is_success() || status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(response);
+            }
+
+            let body = response.text().await?;
+            Err(PistonError::Api {
+                status: status.as_u16(),
+                message: ApiError::message_from(&body),
+            })
+        })
+        .await
+    }
+
+    /// Rebuilds the reqwest client from the current configuration.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Panics
+    /// If the TLS backend can not be initialized, like
+    /// [`reqwest::Client::new`].
+    fn rebuild_client(mut self) -> Self {
+        self.client = self
+            .config
+            .build()
+            .expect("Failed to build the reqwest client");
+        self
+    }
+
+    /// The base url for the Piston V2 API that is being used by this client.
+    ///
+    /// # Returns
+    ///
+    /// - [`String`] - The requested url.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new();
+    ///
+    /// assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+    /// ```
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// The headers being used by this client.
+    ///
+    /// # Returns
+    ///
+    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new();
+    /// let headers = client.get_headers();
+    ///
+    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// ```
+    pub fn get_headers(&self) -> HeaderMap {
+        self.headers.clone()
+    }
+
+    /// Sets a header to send with each request, replacing any existing
+    /// value for the header.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the header.
+    /// - `value` - The value of the header.
+    ///
+    /// # Returns
+    /// - [`Result<(), PistonError>`] - The error, if the name or value
+    ///   is not valid for a header.
+    ///
+    /// # Example
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    ///
+    /// assert!(client.set_header("X-Tenant", "abc").is_ok());
+    /// assert_eq!(client.get_headers().get("X-Tenant").unwrap(), "abc");
+    ///
+    /// assert!(client.set_header("Bad Name", "abc").is_err());
+    /// assert!(client.set_header("X-Tenant", "bad\nvalue").is_err());
+    /// ```
+    pub fn set_header(&mut self, name: &str, value: &str) -> Result<(), PistonError> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| PistonError::InvalidHeader(e.to_string()))?;
+        let value =
+            HeaderValue::from_str(value).map_err(|e| PistonError::InvalidHeader(e.to_string()))?;
+
+        self.headers.insert(name, value);
+        Ok(())
+    }
+
+    /// Removes a header, so it is no longer sent with each request.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the header.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the header was present.
+    ///
+    /// # Example
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    ///
+    /// assert!(client.remove_header("Accept"));
+    /// assert!(!client.get_headers().contains_key("Accept"));
+    /// assert!(!client.remove_header("Accept"));
+    /// ```
+    pub fn remove_header(&mut self, name: &str) -> bool {
+        self.headers.remove(name).is_some()
+    }
+
+    /// Generates the headers the client should use.
+    ///
+    /// # Arguments
+    /// - `key` - The api key to use, if any.
+    ///
+    /// # Returns
+    ///
+    /// - [`Result<HeaderMap, PistonError>`] - A map of Header key,
+    ///   value pairs, or the error if the key is not a valid header
+    ///   value.
+    ///
+    /// # Example
+    /// ```ignore # Fails to compile (private function)
+    /// let headers = piston_rs::Client::generate_headers(None).unwrap();
+    ///
+    /// assert!(!headers.contains_key("Authorization"));
+    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    ///
+    /// let headers = piston_rs::Client::generate_headers(Some("123abc")).unwrap();
+    ///
+    /// assert_eq!(headers.get("Authorization").unwrap(), "123abc");
+    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// ```
+    fn generate_headers(key: Option<&str>) -> Result<HeaderMap, PistonError> {
+        let mut headers = HeaderMap::with_capacity(3);
+        headers.insert("Accept", HeaderValue::from_static("application/json"));
+        headers.insert("User-Agent", HeaderValue::from_static("piston-rs"));
+
+        if let Some(k) = key {
+            let value =
+                HeaderValue::from_str(k).map_err(|e| PistonError::InvalidHeader(e.to_string()))?;
+            headers.insert("Authorization", value);
+        };
+
+        Ok(headers)
+    }
+
+    /// Fetches the runtimes from Piston. **This is an http request**.
+    ///
+    /// If a cache time-to-live was set with
+    /// [`Client::with_runtime_cache_ttl`], runtimes fetched within it
+    /// are returned without contacting Piston. Otherwise, if Piston
+    /// sent an `ETag` with the last runtimes, it is sent back as
+    /// `If-None-Match`, and the cached runtimes are returned if Piston
+    /// responds with `304 Not Modified`. Clones of the client share the
+    /// same cache.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_runtimes() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(runtimes) = client.fetch_runtimes().await {
+    ///     assert!(!runtimes.is_empty());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_runtimes(&self) -> Result<Vec<Runtime>, PistonError> {
+        if let Some(runtimes) = self.fresh_runtimes() {
+            debug!("Using cached runtimes");
+            return Ok(runtimes);
+        }
+
+        self.request_runtimes(true).await
+    }
+
+    /// Fetches the runtimes from Piston, ignoring any cached runtimes,
+    /// and caches the result. **This is an http request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_refresh_runtimes() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache_ttl(Duration::from_secs(300));
+    ///
+    /// if let Ok(runtimes) = client.refresh_runtimes().await {
+    ///     assert!(!runtimes.is_empty());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn refresh_runtimes(&self) -> Result<Vec<Runtime>, PistonError> {
+        self.request_runtimes(false).await
+    }
+
+    /// The cached runtimes, if a cache time-to-live is set and has not
+    /// yet expired.
+    fn fresh_runtimes(&self) -> Option<Vec<Runtime>> {
+        let ttl = self.runtime_cache_ttl?;
+        let cache = self.runtimes_cache.lock().unwrap();
+
+        cache
+            .as_ref()
+            .filter(|c| c.fetched_at.elapsed() < ttl)
+            .map(|c| c.runtimes.clone())
+    }
+
+    /// Sends the runtimes request to Piston and caches the result.
+    ///
+    /// # Arguments
+    /// - `revalidate` - Whether or not to send the cached `ETag`, and
+    ///   accept a `304 Not Modified` response.
+    async fn request_runtimes(&self, revalidate: bool) -> Result<Vec<Runtime>, PistonError> {
+        let endpoint = format!("{}/runtimes", self.url);
+        let request = async {
+            let mut headers = self.headers.clone();
+            let cached = match revalidate {
+                true => self.runtimes_cache.lock().unwrap().clone(),
+                false => None,
+            };
+
+            let etag = cached.as_ref().and_then(|c| c.etag.clone());
+            if let Some(etag) = etag {
+                headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+            }
+
+            let response = self.get_runtimes(&endpoint, &headers).await?;
+
+            if let Some(mut cached) = cached {
+                if cached.etag.is_some() && response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                    cached.fetched_at = Instant::now();
+                    *self.runtimes_cache.lock().unwrap() = Some(cached.clone());
+                    return Ok(cached.runtimes);
+                }
+            }
+
+            let etag = response.headers().get(reqwest::header::ETAG).cloned();
+            let body = response.text().await?;
+            let runtimes = serde_json::from_str::<Vec<Runtime>>(&body)
+                .map_err(|e| PistonError::decode_body(e, &body))?;
+
+            // Without an `ETag` or a time-to-live, a cached entry could
+            // only be revalidated with a stale `ETag`, so it is dropped.
+            let cache = match etag.is_some() || self.runtime_cache_ttl.is_some() {
+                true => Some(CachedRuntimes {
+                    etag,
+                    fetched_at: Instant::now(),
+                    runtimes: runtimes.clone(),
+                }),
+                false => None,
+            };
+
+            *self.runtimes_cache.lock().unwrap() = cache;
+
+            Ok(runtimes)
+        };
+
+        traced!("fetch_runtimes", request, endpoint = endpoint)
+    }
+
+    /// Fetches the runtimes from Piston, sorted by language name and
+    /// then by version. **This is an http request**.
+    ///
+    /// Versions are compared as semver when the `semver` feature is
+    /// enabled and both versions are valid semver. Otherwise they are
+    /// compared lexically. See [`Client::fetch_runtimes`].
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The sorted runtimes
+    ///   or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_runtimes_sorted() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(runtimes) = client.fetch_runtimes_sorted().await {
+    ///     assert!(runtimes.windows(2).all(|w| w[0].language <= w[1].language));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_runtimes_sorted(&self) -> Result<Vec<Runtime>, PistonError> {
+        let mut runtimes = self.fetch_runtimes().await?;
+        runtimes.sort_by(|a, b| {
+            a.language
+                .cmp(&b.language)
+                .then_with(|| compare_versions(&a.version, &b.version))
+        });
+
+        Ok(runtimes)
+    }
+
+    /// Fetches the runtimes from Piston that support the given
+    /// language, by name or alias. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The matching
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_runtimes_filtered() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(runtimes) = client.fetch_runtimes_filtered("py").await {
+    ///     assert!(runtimes.iter().all(|r| r.language == "python"));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_runtimes_filtered(
+        &self,
+        language: &str,
+    ) -> Result<Vec<Runtime>, PistonError> {
+        let mut runtimes = self.fetch_runtimes().await?;
+        runtimes.retain(|r| r.supports(language));
+        Ok(runtimes)
+    }
+
+    /// Fetches the runtimes from Piston, returning the first one that
+    /// matches the predicate. Runtimes after the match are skipped
+    /// rather than deserialized. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `predicate` - The predicate the runtime must match.
+    ///
+    /// # Returns
+    /// - [`Result<Option<Runtime>, PistonError>`] - The matching
+    ///   runtime, if any, or the error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_find_runtime() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(runtime) = client.find_runtime(|r| r.language == "rust").await {
+    ///     assert!(runtime.is_some());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn find_runtime<P>(&self, predicate: P) -> Result<Option<Runtime>, PistonError>
+    where
+        P: FnMut(&Runtime) -> bool,
+    {
+        let endpoint = format!("{}/runtimes", self.url);
+        let request = async {
+            let response = self.get_runtimes(&endpoint, &self.headers).await?;
+            let body = response.bytes().await?;
+
+            Self::find_in_json(&body, predicate)
+        };
+
+        traced!("find_runtime", request, endpoint = endpoint)
+    }
+
+    /// Deserializes runtimes from a JSON array one at a time, stopping
+    /// at the first runtime that matches the predicate.
+    ///
+    /// # Arguments
+    /// - `json` - The JSON array of runtimes.
+    /// - `predicate` - The predicate the runtime must match.
+    ///
+    /// # Returns
+    /// - [`Result<Option<Runtime>, PistonError>`] - The matching
+    ///   runtime, if any, or the error.
+    fn find_in_json<P>(json: &[u8], predicate: P) -> Result<Option<Runtime>, PistonError>
+    where
+        P: FnMut(&Runtime) -> bool,
+    {
+        let mut deserializer = serde_json::Deserializer::from_slice(json);
+        let runtime = RuntimeFinder { predicate }
+            .deserialize(&mut deserializer)
+            .and_then(|r| deserializer.end().map(|_| r))
+            .map_err(|e| PistonError::decode_body(e, &String::from_utf8_lossy(json)))?;
+
+        Ok(runtime)
+    }
+
+    /// Whether or not Piston has any runtimes available, such as for a
+    /// health check. Only the first runtime is deserialized, rather
+    /// than the whole list. **This is an http request**.
+    ///
+    /// # Returns
+    /// - [`Result<bool, PistonError>`] - Whether any runtimes are
+    ///   available or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_runtimes_available() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(available) = client.runtimes_available().await {
+    ///     assert!(available);
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn runtimes_available(&self) -> Result<bool, PistonError> {
+        let runtime = self.find_runtime(|_| true).await?;
+        Ok(runtime.is_some())
+    }
+
+    /// Whether or not Piston has a runtime for the exact language, or
+    /// one of its aliases, and version. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    /// - `version` - The exact version.
+    ///
+    /// # Returns
+    /// - [`Result<bool, PistonError>`] - Whether the runtime is
+    ///   installed or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_has_runtime() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(installed) = client.has_runtime("python", "3.10.0").await {
+    ///     assert!(installed);
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn has_runtime(&self, language: &str, version: &str) -> Result<bool, PistonError> {
+        let runtime = self
+            .find_runtime(|r| r.supports(language) && r.version == version)
+            .await?;
+
+        Ok(runtime.is_some())
+    }
+
+    /// Resolves a language, by name or alias, to the latest runtime
+    /// Piston has for it. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `name` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`Result<Runtime, PistonError>`] - The matching runtime, or
+    ///   [`PistonError::InvalidExecutor`] if the language is unknown.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_resolve_language() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(runtime) = client.resolve_language("py").await {
+    ///     assert_eq!(runtime.language, "python".to_string());
+    /// } else {
+    ///     // The language is unknown, or there was an error contacting
+    ///     // Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn resolve_language(&self, name: &str) -> Result<Runtime, PistonError> {
+        let runtimes = self.fetch_runtimes().await?;
+
+        Runtime::latest(name, &runtimes)
+            .cloned()
+            .ok_or_else(|| PistonError::InvalidExecutor(format!("unknown language '{}'", name)))
+    }
+
+    /// Fetches the names of the languages available on Piston, without
+    /// duplicates across versions. **This is an http request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<String>, PistonError>`] - The available
+    ///   languages or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_languages() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(languages) = client.fetch_languages().await {
+    ///     assert!(languages.contains(&"rust".to_string()));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_languages(&self) -> Result<Vec<String>, PistonError> {
+        let runtimes = self.fetch_runtimes().await?;
+        Ok(Self::unique_languages(&runtimes))
+    }
+
+    /// Collects the language names from the given runtimes, keeping
+    /// the first occurrence of each.
+    ///
+    /// # Arguments
+    /// - `runtimes` - The runtimes to collect from.
+    ///
+    /// # Returns
+    /// - [`Vec<String>`] - The unique language names.
+    fn unique_languages(runtimes: &[Runtime]) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::with_capacity(runtimes.len());
+
+        for runtime in runtimes {
+            if !languages.contains(&runtime.language) {
+                languages.push(runtime.language.clone());
+            }
+        }
+
+        languages
+    }
+
+    /// Executes code using a given executor. **This is an http
+    /// request**.
+    ///
+    /// If Piston responds with a non success status, the error is
+    /// [`PistonError::Api`], containing the message Piston sent.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .set_version("1.50.0")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// if let Ok(response) = client.execute(&executor).await {
+    ///     assert!(response.compile.is_some());
+    ///     assert!(response.run.is_ok());
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute(&self, executor: &Executor) -> Result<ExecResponse, PistonError> {
+        match &self.in_flight {
+            Some(in_flight) => self.execute_coalesced(executor, in_flight).await,
+            None => {
+                self.send_execute(executor, self.headers.clone(), None)
+                    .await
+            }
+        }
+    }
+
+    /// Executes code using a given executor, sharing the response with
+    /// identical executions in flight at the same time. Executions are
+    /// only shared when their request bodies are equal, so an execution
+    /// whose hash collides with another in flight is sent on its own.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `in_flight` - The executions in flight.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn execute_coalesced(
+        &self,
+        executor: &Executor,
+        in_flight: &InFlight,
+    ) -> Result<ExecResponse, PistonError> {
+        let Ok(payload) = serde_json::to_string(&*executor.payload()) else {
+            return self
+                .send_execute(executor, self.headers.clone(), None)
+                .await;
+        };
+
+        let key = executor.content_hash();
+        let cell = match in_flight.lock().unwrap().entry(key) {
+            Entry::Occupied(entry) if entry.get().0 == payload => Some(entry.get().1.clone()),
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => Some(entry.insert((payload, Default::default())).1.clone()),
+        };
+
+        let Some(cell) = cell else {
+            return self
+                .send_execute(executor, self.headers.clone(), None)
+                .await;
+        };
+
+        let result = cell
+            .get_or_try_init(|| self.send_execute(executor, self.headers.clone(), None))
+            .await
+            .cloned();
+
+        let mut in_flight = in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|(_, c)| Arc::ptr_eq(c, &cell))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+
+    /// Executes code using each of the given executors concurrently.
+    /// **These are http requests**.
+    ///
+    /// Each execution succeeds or fails independently, so one failure
+    /// never discards the results of the others. An execution that
+    /// panics fails with [`PistonError::Panicked`]. Use
+    /// [`BatchSummary::from_results`] to count them.
+    ///
+    /// # Arguments
+    /// - `executors` - The executors to use.
+    ///
+    /// # Returns
+    /// - [`Vec<Result<ExecResponse, PistonError>>`] - The response from
+    ///   Piston or the error, for each executor in order.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_batch() {
+    /// let client = piston_rs::Client::new();
+    /// let executors = vec![
+    ///     piston_rs::Executor::new()
+    ///         .set_language("python")
+    ///         .add_file(piston_rs::File::default().set_content("print(1)")),
+    ///     piston_rs::Executor::new()
+    ///         .set_language("python")
+    ///         .add_file(piston_rs::File::default().set_content("print(2)")),
+    /// ];
+    ///
+    /// let results = client.execute_batch(&executors).await;
+    /// let summary = piston_rs::BatchSummary::from_results(&results);
+    ///
+    /// assert_eq!(summary.succeeded + summary.failed, 2);
+    /// # }
+    /// ```
+    pub async fn execute_batch(
+        &self,
+        executors: &[Executor],
+    ) -> Vec<Result<ExecResponse, PistonError>> {
+        future::join_all(executors.iter().map(|e| Self::isolated(self.execute(e)))).await
+    }
+
+    /// Awaits an execution in a batch, turning a panic into an error
+    /// so it does not discard the other executions.
+    ///
+    /// # Arguments
+    /// - `execution` - The execution to await.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The result of the
+    ///   execution, or [`PistonError::Panicked`] if it panicked.
+    async fn isolated(
+        execution: impl Future<Output = Result<ExecResponse, PistonError>>,
+    ) -> Result<ExecResponse, PistonError> {
+        match AssertUnwindSafe(execution).catch_unwind().await {
+            Ok(result) => result,
+            Err(panic) => {
+                let message = match panic.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(panic) => match panic.downcast::<&str>() {
+                        Ok(message) => message.to_string(),
+                        Err(_) => "unknown panic".to_string(),
+                    },
+                };
+
+                Err(PistonError::Panicked(message))
+            }
+        }
+    }
+
+    /// Executes code using each of the given executors concurrently,
+    /// reporting progress as each execution completes. **These are
+    /// http requests**.
+    ///
+    /// The callback is invoked on the awaiting task, after each result
+    /// is received, so it never runs concurrently with itself.
+    ///
+    /// # Arguments
+    /// - `executors` - The executors to use.
+    /// - `on_progress` - Called with the number of completed
+    ///   executions, and the total number of executions.
+    ///
+    /// # Returns
+    /// - [`Vec<Result<ExecResponse, PistonError>>`] - The response from
+    ///   Piston or the error, for each executor in order.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_batch_with_progress() {
+    /// let client = piston_rs::Client::new();
+    /// let executors = vec![
+    ///     piston_rs::Executor::new()
+    ///         .set_language("python")
+    ///         .add_file(piston_rs::File::default().set_content("print(1)"));
+    ///     3
+    /// ];
+    ///
+    /// let results = client
+    ///     .execute_batch_with_progress(&executors, |done, total| {
+    ///         println!("{}%", done * 100 / total);
+    ///     })
+    ///     .await;
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// # }
+    /// ```
+    pub async fn execute_batch_with_progress(
+        &self,
+        executors: &[Executor],
+        on_progress: impl Fn(usize, usize),
+    ) -> Vec<Result<ExecResponse, PistonError>> {
+        let total = executors.len();
+        let mut pending: FuturesUnordered<_> = executors
+            .iter()
+            .enumerate()
+            .map(|(i, e)| async move { (i, Self::isolated(self.execute(e)).await) })
+            .collect();
+
+        let mut results: Vec<Option<Result<ExecResponse, PistonError>>> =
+            executors.iter().map(|_| None).collect();
+        let mut completed = 0;
+
+        while let Some((i, result)) = pending.next().await {
+            results[i] = Some(result);
+            completed += 1;
+            on_progress(completed, total);
+        }
+
+        results.into_iter().flatten().collect()
+    }
+
+    /// Executes code using a given executor against every version of
+    /// its language installed on Piston, concurrently like
+    /// [`Client::execute_batch`]. The executor's version is ignored.
+    /// **These are http requests**.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<(String, ExecResponse)>, PistonError>`] - The
+    ///   response from Piston for each version, or the first error, if
+    ///   any. [`PistonError::InvalidExecutor`] is returned if the
+    ///   language is unknown.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_all_versions() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(responses) = client.execute_all_versions(&executor).await {
+    ///     for (version, response) in responses {
+    ///         println!("{}: {}", version, response.run.stdout);
+    ///     }
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_all_versions(
+        &self,
+        executor: &Executor,
+    ) -> Result<Vec<(String, ExecResponse)>, PistonError> {
+        let versions: Vec<String> = self
+            .fetch_runtimes()
+            .await?
+            .into_iter()
+            .filter(|r| r.supports(&executor.language))
+            .map(|r| r.version)
+            .collect();
+
+        if versions.is_empty() {
+            return Err(PistonError::InvalidExecutor(format!(
+                "unknown language '{}'",
+                executor.language
+            )));
+        }
+
+        let executors: Vec<Executor> = versions
+            .iter()
+            .map(|v| executor.with_language_version(&executor.language, v))
+            .collect();
+
+        versions
+            .into_iter()
+            .zip(self.execute_batch(&executors).await)
+            .map(|(version, result)| result.map(|response| (version, response)))
+            .collect()
+    }
+
+    /// Executes code using a given executor, abandoning the request if
+    /// the token is cancelled before Piston responds. **This is an http
+    /// request**.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `token` - The token used to cancel the request.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response from
+    ///   Piston or the error, which is [`PistonError::Cancelled`] if the
+    ///   token was cancelled.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_cancellable() {
+    /// use tokio_util::sync::CancellationToken;
+    ///
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    ///
+    /// assert!(matches!(
+    ///     client.execute_cancellable(&executor, token).await,
+    ///     Err(piston_rs::PistonError::Cancelled),
+    /// ));
+    /// # }
+    /// ```
+    pub async fn execute_cancellable(
+        &self,
+        executor: &Executor,
+        token: CancellationToken,
+    ) -> Result<ExecResponse, PistonError> {
+        if token.is_cancelled() {
+            return Err(PistonError::Cancelled);
+        }
+
+        let execute = pin!(self.execute(executor));
+        let cancelled = pin!(token.cancelled());
+
+        match future::select(execute, cancelled).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(PistonError::Cancelled),
+        }
+    }
+
+    /// Executes code using a given executor, authorizing with the
+    /// given api key instead of the client's key for this request
+    /// only. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_key() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// if let Ok(response) = client.execute_with_key(&executor, "123abc").await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_key(
+        &self,
+        executor: &Executor,
+        key: &str,
+    ) -> Result<ExecResponse, PistonError> {
+        let headers = self.headers_with_key(key)?;
+        self.send_execute(executor, headers, None).await
+    }
+
+    /// Executes code using a given executor, failing if Piston does not
+    /// respond within the timeout. The timeout applies to this request
+    /// only. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `timeout` - The maximum time to wait for Piston.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_timeout() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// match client.execute_with_timeout(&executor, Duration::from_secs(5)).await {
+    ///     Ok(response) => assert!(response.is_ok()),
+    ///     Err(_) => {
+    ///         // Piston timed out, or there was an error contacting it.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_timeout(
+        &self,
+        executor: &Executor,
+        timeout: Duration,
+    ) -> Result<ExecResponse, PistonError> {
+        self.send_execute(executor, self.headers.clone(), Some(timeout))
+            .await
+    }
+
+    /// Copies the client's headers, overriding the api key.
+    ///
+    /// # Arguments
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Result<HeaderMap, PistonError>`] - The headers, or the
+    ///   error if the key is not a valid header value.
+    fn headers_with_key(&self, key: &str) -> Result<HeaderMap, PistonError> {
+        let value =
+            HeaderValue::from_str(key).map_err(|e| PistonError::InvalidHeader(e.to_string()))?;
+
+        let mut headers = self.headers.clone();
+        headers.insert("Authorization", value);
+        Ok(headers)
+    }
+
+    /// Executes a hand built JSON request body on Piston, for request
+    /// fields [`Executor`] does not model yet. The body is sent as is,
+    /// without any validation. **This is an http request**.
+    ///
+    /// # Arguments
+    /// - `body` - The JSON request body to send.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_raw() {
+    /// let client = piston_rs::Client::new();
+    /// let body = serde_json::json!({
+    ///     "language": "python",
+    ///     "version": "*",
+    ///     "files": [{"content": "print(42)"}],
+    /// });
+    ///
+    /// if let Ok(response) = client.execute_raw(body).await {
+    ///     assert_eq!(response.run.stdout, "42\n");
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_raw(&self, body: serde_json::Value) -> Result<ExecResponse, PistonError> {
+        self.send_json(body, self.headers.clone(), None).await
+    }
+
+    /// Sends the executor to Piston with the given headers.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to send.
+    /// - `headers` - The headers to send with the request.
+    /// - `timeout` - The timeout for the request, if any.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn send_execute(
+        &self,
+        executor: &Executor,
+        headers: HeaderMap,
+        timeout: Option<Duration>,
+    ) -> Result<ExecResponse, PistonError> {
+        if let Some(max) = self.max_stdin {
+            if executor.stdin_bytes() > max {
+                return Err(PistonError::InvalidExecutor(format!(
+                    "stdin is {} bytes, exceeding the limit of {} bytes",
+                    executor.stdin_bytes(),
+                    max
+                )));
+            }
+        }
+
+        let mut body = serde_json::to_value(&*executor.payload())?;
+        if let Some(version) = &self.default_version {
+            if executor.version == "*" {
+                body["version"] = serde_json::Value::from(version.as_str());
+            }
+        }
+
+        self.send_json(body, headers, timeout).await
+    }
+
+    /// Sends a JSON execute request body to Piston with the given
+    /// headers.
+    ///
+    /// # Arguments
+    /// - `body` - The request body to send.
+    /// - `headers` - The headers to send with the request.
+    /// - `timeout` - The timeout for the request, if any.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn send_json(
+        &self,
+        body: serde_json::Value,
+        headers: HeaderMap,
+        timeout: Option<Duration>,
+    ) -> Result<ExecResponse, PistonError> {
+        let mut headers = headers;
+        if self.idempotency {
+            let id = Self::generate_request_id();
+            // The id is only ever hex characters, so it is always valid.
+            headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&id).unwrap());
+        }
+
+        let endpoint = format!("{}/execute", self.url);
+        let request = Self::retry(self.write_retries, || {
+            self.post_execute(&endpoint, &body, headers.clone(), timeout)
+        });
+
+        traced!(
+            "execute",
+            request,
+            endpoint = endpoint,
+            language = body["language"].as_str().unwrap_or_default(),
+            version = body["version"].as_str().unwrap_or_default(),
+        )
+    }
+
+    /// Sends the request body to the given Piston endpoint, compressed
+    /// if enabled and accepted by Piston.
+    ///
+    /// # Arguments
+    /// - `endpoint` - The execute endpoint.
+    /// - `body` - The request body to send.
+    /// - `headers` - The headers to send with the request.
+    /// - `timeout` - The timeout for the request, if any.
+    ///
+    /// # Returns
+    /// - [`Result<reqwest::Response, reqwest::Error>`] - The response
+    ///   from Piston or the error, if any.
+    async fn send_body(
+        &self,
+        endpoint: &str,
+        body: &serde_json::Value,
+        headers: HeaderMap,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let with_timeout = |request: reqwest::RequestBuilder| match timeout {
+            Some(t) => request.timeout(t),
+            None => request,
+        };
+
+        #[cfg(feature = "compression")]
+        if let Some(accepted) = &self.compression {
+            if accepted.load(Ordering::Relaxed) {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                // Writing to a Vec never fails.
+                encoder.write_all(body.to_string().as_bytes()).unwrap();
+
+                let request = self
+                    .client
+                    .post(endpoint)
+                    .headers(headers.clone())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header(reqwest::header::CONTENT_ENCODING, "gzip")
+                    .body(encoder.finish().unwrap());
+
+                let response = with_timeout(request).send().await?;
+                if response.status() != reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                    return Ok(response);
+                }
+
+                debug!("Piston rejected a compressed body, retrying uncompressed");
+                accepted.store(false, Ordering::Relaxed);
+                self.throttle().await;
+            }
+        }
+
+        let request = self.client.post(endpoint).headers(headers).json(body);
+        with_timeout(request).send().await
+    }
+
+    /// Posts the request body to the given Piston endpoint.
+    ///
+    /// # Arguments
+    /// - `endpoint` - The execute endpoint.
+    /// - `body` - The request body to send.
+    /// - `headers` - The headers to send with the request.
+    /// - `timeout` - The timeout for the request, if any.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn post_execute(
+        &self,
+        endpoint: &str,
+        body: &serde_json::Value,
+        headers: HeaderMap,
+        timeout: Option<Duration>,
+    ) -> Result<ExecResponse, PistonError> {
+        self.throttle().await;
+        debug!("Executing at {} with body {}", endpoint, body);
+
+        match self.send_body(endpoint, body, headers, timeout).await {
+            Ok(data) => {
+                let status = data.status();
+                debug!("Received status {} from Piston", status);
+                record_status!(status);
+
+                match status {
+                    reqwest::StatusCode::OK => {
+                        let text = data.text().await?;
+                        let mut response = serde_json::from_str::<RawExecResponse>(&text)
+                            .map_err(|e| PistonError::decode_body(e, &text))?;
+                        let binary = body["binary_output"].as_bool().unwrap_or(false);
+                        response.run.binary = binary;
+                        for run in response.runs.iter_mut().flatten() {
+                            run.binary = binary;
+                        }
+
+                        Ok(ExecResponse {
+                            language: response.language,
+                            version: response.version,
+                            run: response.run,
+                            runs: response.runs,
+                            compile: response.compile,
+                            status: status.as_u16(),
+                            compile_timeout: response.compile_timeout,
+                            run_timeout: response.run_timeout,
+                            compile_memory_limit: response.compile_memory_limit,
+                            run_memory_limit: response.run_memory_limit,
+                        })
+                    }
+                    _ => {
+                        let body = data.text().await?;
+
+                        Err(PistonError::Api {
+                            status: status.as_u16(),
+                            message: ApiError::message_from(&body),
+                        })
+                    }
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// A builder for a [`Client`], for configuring several options at once.
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    /// The base url for Piston.
+    url: String,
+    /// The api key to use, if any.
+    key: Option<String>,
+    /// The user agent to send with each request.
+    user_agent: String,
+    /// The configuration used to build the reqwest client.
+    config: HttpConfig,
+    /// The maximum requests per second, or `0` for no limit.
+    rate_limit: u32,
+    /// Whether or not to send a request ID with each execution.
+    idempotency: bool,
+    /// Whether or not identical concurrent executions are coalesced.
+    coalescing: bool,
+    /// The number of times to retry fetching runtimes.
+    read_retries: u32,
+    /// The number of times to retry executions.
+    write_retries: u32,
+    /// Whether or not to compress execute request bodies.
+    #[cfg(feature = "compression")]
+    compression: bool,
+    /// The maximum size of `stdin` in bytes, if limited.
+    max_stdin: Option<usize>,
+    /// The version to execute with when an executor uses `"*"`, if any.
+    default_version: Option<String>,
+    /// How long fetched runtimes are reused, if cached.
+    runtime_cache_ttl: Option<Duration>,
+    /// The url of the proxy to route requests through, if any.
+    proxy: Option<String>,
+}
+
+impl Default for ClientBuilder {
+    /// Creates a new client builder. Alias for [`ClientBuilder::new`].
+    ///
+    /// # Returns
+    /// - [`ClientBuilder`] - The new ClientBuilder.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a new client builder, using the public Piston instance
+    /// and no api key.
+    ///
+    /// # Returns
+    /// - [`ClientBuilder`] - The new ClientBuilder.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new().build().unwrap();
+    ///
+    /// assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            key: None,
+            user_agent: "piston-rs".to_string(),
+            config: HttpConfig::default(),
+            rate_limit: 0,
+            idempotency: false,
+            coalescing: false,
+            read_retries: DEFAULT_READ_RETRIES,
+            write_retries: 0,
+            #[cfg(feature = "compression")]
+            compression: false,
+            max_stdin: None,
+            default_version: None,
+            runtime_cache_ttl: None,
+            proxy: None,
+        }
+    }
+
+    /// Builds the [`Client`].
+    ///
+    /// # Returns
+    /// - [`Result<Client, PistonError>`] - The new Client, or the error
+    ///   if the key or user agent is not a valid header value, the
+    ///   proxy url is not valid, or the TLS backend can not be
+    ///   initialized.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .set_user_agent("my-app/1.0")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(client.get_headers().get("User-Agent").unwrap(), "my-app/1.0");
+    ///
+    /// let result = piston_rs::ClientBuilder::new()
+    ///     .set_key("bad\nkey")
+    ///     .build();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn build(mut self) -> Result<Client, PistonError> {
+        let invalid =
+            |e: reqwest::header::InvalidHeaderValue| PistonError::InvalidHeader(e.to_string());
+
+        let mut headers = Client::generate_headers(self.key.as_deref())?;
+        headers.insert(
+            "User-Agent",
+            HeaderValue::from_str(&self.user_agent).map_err(invalid)?,
+        );
+
+        if let Some(url) = &self.proxy {
+            self.config.proxy = Some(reqwest::Proxy::all(url)?);
+        }
+
+        let limiter = match self.rate_limit {
+            0 => None,
+            n => Some(RateLimiter::per_second(n)),
+        };
+
+        Ok(Client {
+            url: self.url,
+            client: self.config.build()?,
+            headers,
+            config: self.config,
+            limiter,
+            idempotency: self.idempotency,
+            read_retries: self.read_retries,
+            write_retries: self.write_retries,
+            max_stdin: self.max_stdin,
+            default_version: self.default_version,
+            runtime_cache_ttl: self.runtime_cache_ttl,
+            runtimes_cache: Arc::new(StdMutex::new(None)),
+            in_flight: self.coalescing.then(InFlight::default),
+            #[cfg(feature = "compression")]
+            compression: self.compression.then(|| Arc::new(AtomicBool::new(true))),
+        })
+    }
+
+    /// Sets the url of the Piston instance to use.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use as the underlying piston backend.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Sets the api key to send with each request.
+    ///
+    /// # Arguments
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    /// Sets the user agent to send with each request. Defaults to
+    /// `piston-rs`.
+    ///
+    /// # Arguments
+    /// - `user_agent` - The user agent to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Sets the timeout for each request. See
+    /// [`Client::execute_with_timeout`] to override it for a single
+    /// execution. By default, requests do not time out.
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections the client keeps
+    /// open per host. See [`Client::with_pool_max_idle_per_host`].
+    ///
+    /// # Arguments
+    /// - `max` - The maximum idle connections.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long the client keeps idle connections open. See
+    /// [`Client::with_pool_idle_timeout`].
+    ///
+    /// # Arguments
+    /// - `timeout` - The idle timeout.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends all requests using the given reqwest client. The pool,
+    /// timeout, and proxy options of this builder are not applied to
+    /// it. See [`Client::with_reqwest_client`].
+    ///
+    /// # Arguments
+    /// - `client` - The reqwest client to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_reqwest_client(mut self, client: reqwest::Client) -> Self {
+        self.config.client = Some(client);
+        self
+    }
+
+    /// Limits the number of requests the client sends each second. See
+    /// [`Client::with_rate_limit`].
+    ///
+    /// # Arguments
+    /// - `per_second` - The maximum requests per second. `0` removes
+    ///   the limit.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = per_second;
+        self
+    }
+
+    /// Sets whether or not to send a request ID with each execution.
+    /// See [`Client::with_idempotency`].
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to send request IDs.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_idempotency(mut self, enabled: bool) -> Self {
+        self.idempotency = enabled;
+        self
+    }
+
+    /// Sets the number of times fetching runtimes is retried. See
+    /// [`Client::with_read_retries`].
+    ///
+    /// # Arguments
+    /// - `retries` - The number of retries, or `0` to never retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_read_retries(mut self, retries: u32) -> Self {
+        self.read_retries = retries;
+        self
+    }
+
+    /// Sets the number of times executions are retried. See
+    /// [`Client::with_write_retries`].
+    ///
+    /// # Arguments
+    /// - `retries` - The number of retries, or `0` to never retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_write_retries(mut self, retries: u32) -> Self {
+        self.write_retries = retries;
+        self
+    }
+
+    /// Sets whether or not identical concurrent executions share a
+    /// single request. See [`Client::with_request_coalescing`].
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to coalesce executions.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_request_coalescing(mut self, enabled: bool) -> Self {
+        self.coalescing = enabled;
+        self
+    }
+
+    /// Sets whether or not execute request bodies are compressed. See
+    /// [`Client::with_request_compression`].
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to compress request bodies.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[cfg(feature = "compression")]
+    #[must_use]
+    pub fn set_request_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Sets the maximum size of `stdin` in bytes. See
+    /// [`Client::with_max_stdin`].
+    ///
+    /// # Arguments
+    /// - `bytes` - The maximum size of `stdin` in bytes.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_max_stdin(mut self, bytes: usize) -> Self {
+        self.max_stdin = Some(bytes);
+        self
+    }
+
+    /// Sets the version to execute with when an executor's version is
+    /// `"*"`. See [`Client::with_default_version`].
+    ///
+    /// # Arguments
+    /// - `version` - The version to execute with by default.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_default_version(mut self, version: &str) -> Self {
+        self.default_version = Some(version.to_string());
+        self
+    }
+
+    /// Sets how long fetched runtimes are reused. See
+    /// [`Client::with_runtime_cache_ttl`].
+    ///
+    /// # Arguments
+    /// - `ttl` - How long fetched runtimes are reused.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_runtime_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.runtime_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the http proxy to route all requests through. The url is
+    /// validated when the client is built. See [`Client::with_proxy`].
+    ///
+    /// # Arguments
+    /// - `proxy_url` - The url of the proxy.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_proxy(mut self, proxy_url: &str) -> Self {
+        self.proxy = Some(proxy_url.to_string());
+        self
+    }
+}
+
+/// Visits a sequence of runtimes, short circuiting on the first one
+/// that matches the predicate.
+struct RuntimeFinder<P> {
+    predicate: P,
+}
+
+impl<'de, P> DeserializeSeed<'de> for RuntimeFinder<P>
+where
+    P: FnMut(&Runtime) -> bool,
+{
+    type Value = Option<Runtime>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, P> Visitor<'de> for RuntimeFinder<P>
+where
+    P: FnMut(&Runtime) -> bool,
+{
+    type Value = Option<Runtime>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of runtimes")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(runtime) = seq.next_element::<Runtime>()? {
+            if (self.predicate)(&runtime) {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                return Ok(Some(runtime));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test_client_private {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures_util::FutureExt;
+    use tokio::sync::OnceCell;
+
+    use super::BatchSummary;
+    use super::CancellationToken;
+    use super::Client;
+    use super::ExecResponse;
+    use super::Executor;
+    use super::PistonError;
+    use super::Runtime;
+    use crate::mock::{MockResponse, MockServer};
+
+    /// A runtimes response body.
+    const RUNTIMES_BODY: &str = r#"[
+        {"language": "python", "version": "2.7.18", "aliases": ["py", "python2"]},
+        {"language": "rust", "version": "1.50.0", "aliases": ["rs"]},
+        {"language": "python", "version": "3.10.0", "aliases": ["py", "python3"]}
+    ]"#;
+
+    /// A successful execute response body.
+    const EXEC_BODY: &str = r#"{
+        "language": "rust",
+        "version": "1.50.0",
+        "run": {"stdout": "42", "stderr": "", "output": "42", "code": 0, "signal": null}
+    }"#;
+
+    #[test]
+    fn test_gen_headers_no_key() {
+        let headers = Client::generate_headers(None).unwrap();
+
+        assert!(!headers.contains_key("Authorization"));
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    }
+
+    #[test]
+    fn test_gen_headers_with_key() {
+        let headers = Client::generate_headers(Some("123abc")).unwrap();
+
+        assert_eq!(headers.get("Authorization").unwrap(), "123abc");
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    }
+
+    #[test]
+    fn test_gen_headers_invalid_key() {
+        match Client::generate_headers(Some("123\nabc")) {
+            Err(PistonError::InvalidHeader(_)) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_with_key_invalid() {
+        match Client::with_key("123\nabc") {
+            Err(PistonError::InvalidHeader(_)) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert!(Client::with_url_and_key("http://localhost:3000", "123\nabc").is_err());
+    }
+
+    #[test]
+    fn test_unique_languages() {
+        let runtime = |language: &str, version: &str| Runtime {
+            language: language.to_string(),
+            version: version.to_string(),
+            aliases: vec![],
+        };
+
+        let runtimes = vec![
+            runtime("python", "2.7.18"),
+            runtime("rust", "1.50.0"),
+            runtime("python", "3.10.0"),
+            runtime("rust", "1.68.2"),
+        ];
+
+        let languages = Client::unique_languages(&runtimes);
+        assert_eq!(languages, vec!["python".to_string(), "rust".to_string()]);
+    }
+
+    #[test]
+    fn test_find_in_json_short_circuits() {
+        // The last entry is not a valid runtime, so it would fail to
+        // deserialize if processing did not stop at the match.
+        let json = br#"[
+            {"language": "python", "version": "3.10.0", "aliases": ["py"]},
+            {"language": "rust", "version": "1.50.0", "aliases": ["rs"]},
+            {"language": 42}
+        ]"#;
+
+        let mut visited = 0;
+        let runtime = Client::find_in_json(json, |r| {
+            visited += 1;
+            r.language == "rust"
+        })
+        .unwrap();
+
+        assert_eq!(visited, 2);
+        assert_eq!(runtime.unwrap().version, "1.50.0".to_string());
+    }
+
+    #[test]
+    fn test_find_in_json_no_match() {
+        let json = br#"[{"language": "python", "version": "3.10.0", "aliases": []}]"#;
+        let runtime = Client::find_in_json(json, |r| r.language == "rust").unwrap();
+
+        assert!(runtime.is_none());
+    }
+
+    #[test]
+    fn test_find_in_json_invalid() {
+        let runtime = Client::find_in_json(b"{}", |_| true);
+
+        assert!(runtime.is_err());
+    }
+
+    #[test]
+    fn test_clone() {
+        let client = Client::with_url_and_key("http://localhost:3000", "123abc").unwrap();
+        let cloned = client.clone();
+
+        assert_eq!(cloned.get_url(), client.get_url());
+        assert_eq!(cloned.get_headers(), client.get_headers());
+    }
+
+    #[test]
+    fn test_headers_with_key() {
+        let client = Client::with_key("123abc").unwrap();
+        let headers = client.headers_with_key("tenant-key").unwrap();
+
+        assert_eq!(headers.get("Authorization").unwrap(), "tenant-key");
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_key_sends_key() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url_and_key(&server.url, "123abc").unwrap();
+        let executor = Executor::new().set_language("rust");
+
+        let response = client.execute_with_key(&executor, "tenant-key").await;
+        assert!(response.unwrap().is_ok());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/execute".to_string());
+        assert_eq!(requests[0].header("Authorization"), Some("tenant-key"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_idempotency() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_idempotency(true);
+        let executors = vec![Executor::new().set_language("rust"); 2];
+
+        let results = client.execute_batch(&executors).await;
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        let ids: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|r| r.header("X-Request-Id").unwrap().to_string())
+            .collect();
+
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+        assert!(ids
+            .iter()
+            .all(|id| id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit())));
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_idempotency() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+
+        assert!(client.execute(&executor).await.is_ok());
+        assert!(server.requests()[0].header("X-Request-Id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_raw() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url);
+        let body = serde_json::json!({
+            "language": "rust",
+            "version": "1.50.0",
+            "files": [{"content": "fn main() {}"}],
+            "experimental_flag": true,
+        });
+
+        let response = client.execute_raw(body.clone()).await.unwrap();
+        assert_eq!(response.run.stdout, "42".to_string());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/execute".to_string());
+
+        let sent: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(sent, body);
+    }
+
+    #[tokio::test]
+    async fn test_execute_malformed_body() {
+        let body = r#"{"language": "rust", "run": {"code": "#;
+        let server = MockServer::start(vec![MockResponse::new(200, body)]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+
+        match client.execute(&executor).await {
+            Err(PistonError::Decode {
+                details,
+                body: Some(raw),
+            }) => {
+                assert!(!details.is_empty());
+                assert_eq!(raw, body.to_string());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_malformed_body() {
+        let server = MockServer::start(vec![MockResponse::new(200, "<html>oops</html>")]);
+        let client = Client::with_url(&server.url);
+
+        match client.fetch_runtimes().await {
+            Err(PistonError::Decode { body, .. }) => {
+                assert_eq!(body, Some("<html>oops</html>".to_string()));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_max_stdin() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_max_stdin(4);
+
+        let executor = Executor::new().set_language("rust").set_stdin("1234");
+        assert!(client.execute(&executor).await.is_ok());
+
+        let executor = executor.set_stdin("12345");
+        match client.execute(&executor).await {
+            Err(PistonError::InvalidExecutor(details)) => {
+                assert_eq!(details, "stdin is 5 bytes, exceeding the limit of 4 bytes");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_default_version() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_default_version("1.50.0");
+
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_ok());
+        assert!(client
+            .execute(&executor.set_version("1.68.2"))
+            .await
+            .is_ok());
+
+        let versions: Vec<serde_json::Value> = server
+            .requests()
+            .iter()
+            .map(|r| {
+                serde_json::from_slice::<serde_json::Value>(&r.body).unwrap()["version"].clone()
+            })
+            .collect();
+
+        assert_eq!(versions, vec!["1.50.0", "1.68.2"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_reqwest_client() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        default_headers.insert("X-Middleware", "metrics".parse().unwrap());
+
+        let reqwest_client = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .build()
+            .unwrap();
+
+        let client = Client::with_url_and_key(&server.url, "123abc")
+            .unwrap()
+            .with_reqwest_client(reqwest_client);
+
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_ok());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/execute".to_string());
+        assert_eq!(request.header("X-Middleware"), Some("metrics"));
+        assert_eq!(request.header("Authorization"), Some("123abc"));
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_kept_after_options() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        default_headers.insert("X-Middleware", "metrics".parse().unwrap());
+
+        let reqwest_client = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .build()
+            .unwrap();
+
+        let client = Client::with_url(&server.url)
+            .with_reqwest_client(reqwest_client.clone())
+            .with_pool_max_idle_per_host(1)
+            .with_pool_idle_timeout(Duration::from_secs(30))
+            .with_proxy("http://127.0.0.1:1")
+            .unwrap();
+
+        let built = Client::builder()
+            .set_url(&server.url)
+            .set_reqwest_client(reqwest_client)
+            .set_pool_max_idle_per_host(1)
+            .build()
+            .unwrap();
+
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_ok());
+        assert!(built.execute(&executor).await.is_ok());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|r| r.header("X-Middleware") == Some("metrics")));
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_coalescing() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_millis(200))
+        ]);
+        let client = Client::with_url(&server.url).with_request_coalescing(true);
+        let executor = Executor::new().set_language("rust");
+
+        let results =
+            futures_util::future::join_all((0..5).map(|_| client.execute(&executor))).await;
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert_eq!(server.requests().len(), 1);
+
+        assert!(client.execute(&executor).await.is_ok());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_coalescing_distinct() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_millis(100))
+        ]);
+        let client = Client::with_url(&server.url).with_request_coalescing(true);
+        let first = Executor::new().set_language("rust");
+        let second = first.clone().set_stdin("42");
+
+        let (a, b) =
+            futures_util::future::join(client.execute(&first), client.execute(&second)).await;
+        assert!(a.is_ok() && b.is_ok());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_coalescing_collision() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_request_coalescing(true);
+        let executor = Executor::new().set_language("rust");
+
+        let other: ExecResponse = serde_json::from_str(
+            r#"{
+                "language": "rust",
+                "version": "1.50.0",
+                "run": {"stdout": "secret", "stderr": "", "output": "secret", "code": 0, "signal": null},
+                "status": 200
+            }"#,
+        )
+        .unwrap();
+
+        // Another execution in flight under the same key, but with a
+        // different request body, as if their hashes collided.
+        let in_flight = client.in_flight.clone().unwrap();
+        let cell = Arc::new(OnceCell::new_with(Some(other)));
+        in_flight
+            .lock()
+            .unwrap()
+            .insert(executor.content_hash(), ("{}".to_string(), cell));
+
+        let response = client.execute(&executor).await.unwrap();
+        assert_eq!(response.run.stdout, "42".to_string());
+        assert_eq!(server.requests().len(), 1);
+        assert!(in_flight
+            .lock()
+            .unwrap()
+            .contains_key(&executor.content_hash()));
+    }
+
+    #[tokio::test]
+    async fn test_execute_api_error() {
+        let server = MockServer::start(vec![
+            MockResponse::new(400, r#"{"message": "rust-0.0.1 runtime is unknown"}"#),
+            MockResponse::new(502, "Bad Gateway"),
+        ]);
+
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust").set_version("0.0.1");
+
+        match client.execute(&executor).await {
+            Err(PistonError::Api { status, message }) => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "rust-0.0.1 runtime is unknown".to_string());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        match client.execute(&executor).await {
+            Err(PistonError::Api { status, message }) => {
+                assert_eq!(status, 502);
+                assert_eq!(message, "Bad Gateway".to_string());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_filtered() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        let runtimes = client.fetch_runtimes_filtered("py").await.unwrap();
+        let versions: Vec<&str> = runtimes.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, vec!["2.7.18", "3.10.0"]);
+
+        let runtimes = client.fetch_runtimes_filtered("go").await.unwrap();
+        assert!(runtimes.is_empty());
+
+        assert_eq!(server.requests()[0].path, "/runtimes".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_not_modified() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, RUNTIMES_BODY).with_header("ETag", "\"v1\""),
+            MockResponse::new(304, ""),
+        ]);
+        let client = Client::with_url(&server.url);
+
+        let first = client.fetch_runtimes().await.unwrap();
+        let second = client.fetch_runtimes().await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(second.len(), 3);
+
+        let requests = server.requests();
+        assert!(requests[0].header("If-None-Match").is_none());
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_without_etag() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        assert!(client.fetch_runtimes().await.is_ok());
+        assert!(client.fetch_runtimes().await.is_ok());
+        assert!(server.requests()[1].header("If-None-Match").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_etag_dropped() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, RUNTIMES_BODY).with_header("ETag", "\"v1\""),
+            MockResponse::new(200, "[]"),
+            MockResponse::new(304, ""),
+        ]);
+        let client = Client::with_url(&server.url);
+
+        assert_eq!(client.fetch_runtimes().await.unwrap().len(), 3);
+        assert!(client.fetch_runtimes().await.unwrap().is_empty());
+        assert!(client.runtimes_cache.lock().unwrap().is_none());
+
+        let _ = client.fetch_runtimes().await;
+
+        let requests = server.requests();
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+        assert!(requests[2].header("If-None-Match").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_sorted() {
+        let body = r#"[
+            {"language": "rust", "version": "1.50.0", "aliases": ["rs"]},
+            {"language": "python", "version": "3.10.0", "aliases": ["py"]},
+            {"language": "bash", "version": "5.1.0", "aliases": ["sh"]},
+            {"language": "python", "version": "2.7.18", "aliases": ["py"]}
+        ]"#;
+        let server = MockServer::start(vec![MockResponse::new(200, body)]);
+        let client = Client::with_url(&server.url);
+
+        let runtimes = client.fetch_runtimes_sorted().await.unwrap();
+        let sorted: Vec<String> = runtimes.iter().map(|r| r.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "bash@5.1.0",
+                "python@2.7.18",
+                "python@3.10.0",
+                "rust@1.50.0"
+            ]
+        );
+
+        let unsorted = client.fetch_runtimes().await.unwrap();
+        assert_eq!(unsorted[0].language, "rust".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_retried_by_default() {
+        let server = MockServer::start(vec![
+            MockResponse::new(503, "Service Unavailable"),
+            MockResponse::new(200, RUNTIMES_BODY),
+        ]);
+        let client = Client::with_url(&server.url);
+
+        assert_eq!(client.fetch_runtimes().await.unwrap().len(), 3);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_retries_exhausted() {
+        let server = MockServer::start(vec![MockResponse::new(503, "Service Unavailable")]);
+        let client = Client::with_url(&server.url).with_read_retries(1);
+
+        match client.fetch_runtimes().await {
+            Err(PistonError::Api { status, .. }) => assert_eq!(status, 503),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_not_retried_by_default() {
+        let server = MockServer::start(vec![
+            MockResponse::new(503, "Service Unavailable"),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+
+        match client.execute(&executor).await {
+            Err(PistonError::Api { status, .. }) => assert_eq!(status, 503),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_write_retries_reuse_request_id() {
+        let server = MockServer::start(vec![
+            MockResponse::new(503, "Service Unavailable"),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+        let client = Client::with_url(&server.url)
+            .with_idempotency(true)
+            .with_write_retries(1);
+        let executor = Executor::new().set_language("rust");
+
+        assert!(client.execute(&executor).await.is_ok());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].header("X-Request-Id").is_some());
+        assert_eq!(
+            requests[0].header("X-Request-Id"),
+            requests[1].header("X-Request-Id")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_runtimes_cache_ttl() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let ttl = Duration::from_secs(60);
+        let client = Client::with_url(&server.url).with_runtime_cache_ttl(ttl);
+
+        let first = client.fetch_runtimes().await.unwrap();
+        assert_eq!(client.fetch_runtimes().await.unwrap(), first);
+        assert_eq!(server.requests().len(), 1);
+
+        tokio::time::advance(ttl - Duration::from_secs(1)).await;
+        assert_eq!(client.fetch_runtimes().await.unwrap(), first);
+        assert_eq!(server.requests().len(), 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(client.fetch_runtimes().await.unwrap(), first);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_runtimes() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, RUNTIMES_BODY).with_header("ETag", "\"v1\""),
+            MockResponse::new(200, "[]"),
+        ]);
+        let client = Client::with_url(&server.url).with_runtime_cache_ttl(Duration::from_secs(60));
+
+        assert_eq!(client.fetch_runtimes().await.unwrap().len(), 3);
+        assert!(client.refresh_runtimes().await.unwrap().is_empty());
+        assert!(client.fetch_runtimes().await.unwrap().is_empty());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].header("If-None-Match").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_runtimes_available() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, RUNTIMES_BODY),
+            MockResponse::new(200, "[]"),
+        ]);
+        let client = Client::with_url(&server.url);
+
+        assert!(client.runtimes_available().await.unwrap());
+        assert!(!client.runtimes_available().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_has_runtime() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        assert!(client.has_runtime("python", "3.10.0").await.unwrap());
+        assert!(client.has_runtime("py", "2.7.18").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_has_runtime_absent_version() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        assert!(!client.has_runtime("python", "3.11.0").await.unwrap());
+        assert!(!client.has_runtime("rust", "3.10.0").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_language_alias() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        let runtime = client.resolve_language("py").await.unwrap();
+        assert_eq!(runtime.language, "python".to_string());
+        assert_eq!(runtime.version, "3.10.0".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_language_unknown() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+
+        match client.resolve_language("cobol").await {
+            Err(PistonError::InvalidExecutor(message)) => assert!(message.contains("cobol")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_versions() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, RUNTIMES_BODY),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("py").set_version("1.0.0");
+
+        let responses = client.execute_all_versions(&executor).await.unwrap();
+        let versions: Vec<&str> = responses.iter().map(|(v, _)| v.as_str()).collect();
+        assert_eq!(versions, vec!["2.7.18", "3.10.0"]);
+
+        let mut sent: Vec<String> = server.requests()[1..]
+            .iter()
+            .map(|r| {
+                let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                body["version"].as_str().unwrap().to_string()
+            })
+            .collect();
+        sent.sort();
+        assert_eq!(sent, vec!["2.7.18", "3.10.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_versions_unknown_language() {
+        let server = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("cobol");
+
+        match client.execute_all_versions(&executor).await {
+            Err(PistonError::InvalidExecutor(message)) => assert!(message.contains("cobol")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_partial_failure() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY),
+            MockResponse::new(500, "Internal Server Error"),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+
+        let client = Client::with_url(&server.url);
+        let executors = vec![Executor::new().set_language("rust"); 3];
+
+        let results = client.execute_batch(&executors).await;
+        assert_eq!(results.len(), 3);
+
+        let summary = BatchSummary::from_results(&results);
+        assert_eq!(
+            summary,
+            BatchSummary {
+                succeeded: 2,
+                failed: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_isolates_panics() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+
+        let results = futures_util::future::join_all([
+            Client::isolated(client.execute(&executor)).boxed(),
+            Client::isolated(async { panic!("grader crashed") }).boxed(),
+        ])
+        .await;
+
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(PistonError::Panicked(message)) => assert_eq!(message, "grader crashed"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_with_progress() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_millis(100)),
+            MockResponse::new(500, "Internal error"),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+        let client = Client::with_url(&server.url);
+        let executors = vec![Executor::new().set_language("rust"); 3];
+
+        let ticks = std::sync::Mutex::new(vec![]);
+        let results = client
+            .execute_batch_with_progress(&executors, |done, total| {
+                ticks.lock().unwrap().push((done, total));
+            })
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(ticks.into_inner().unwrap(), vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_builder_all_options() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_millis(500))
+        ]);
+
+        let client = Client::builder()
+            .set_url(&server.url)
+            .set_key("123abc")
+            .set_user_agent("my-app/1.0")
+            .set_timeout(Duration::from_millis(50))
+            .set_pool_max_idle_per_host(4)
+            .set_pool_idle_timeout(Duration::from_secs(30))
+            .set_rate_limit(5)
+            .set_idempotency(true)
+            .set_request_coalescing(true)
+            .set_read_retries(3)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_url(), server.url);
+        assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+        assert_eq!(
+            client.get_headers().get("User-Agent").unwrap(),
+            "my-app/1.0"
+        );
+        assert_eq!(client.config.timeout, Some(Duration::from_millis(50)));
+        assert_eq!(client.config.pool_max_idle_per_host, Some(4));
+        assert_eq!(
+            client.config.pool_idle_timeout,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            client.limiter.as_ref().unwrap().period,
+            Duration::from_millis(200)
+        );
+        assert!(client.idempotency);
+        assert!(client.in_flight.is_some());
+        assert_eq!(client.read_retries, 3);
+        assert_eq!(client.write_retries, 0);
+
+        let executor = Executor::new().set_language("rust");
+        match client.execute(&executor).await {
+            Err(PistonError::Http(e)) => assert!(e.is_timeout()),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("User-Agent"), Some("my-app/1.0"));
+        assert!(request.header("X-Request-Id").is_some());
     }
 
     #[test]
-    fn test_gen_headers_with_key() {
-        let headers = Client::generate_headers(Some("123abc"));
+    fn test_builder_invalid_user_agent() {
+        let result = Client::builder().set_user_agent("bad\nagent").build();
 
-        assert_eq!(headers.get("Authorization").unwrap(), "123abc");
-        assert_eq!(headers.get("Accept").unwrap(), "application/json");
-        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+        assert!(matches!(result, Err(PistonError::InvalidHeader(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_proxy() {
+        let proxy = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url("http://piston.invalid/api/v2/piston")
+            .with_proxy(&proxy.url)
+            .unwrap();
+
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_ok());
+
+        let requests = proxy.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].path,
+            "http://piston.invalid/api/v2/piston/execute".to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_with_proxy() {
+        let proxy = MockServer::start(vec![MockResponse::new(200, RUNTIMES_BODY)]);
+        let client = Client::builder()
+            .set_url("http://piston.invalid/api/v2/piston")
+            .set_proxy(&proxy.url)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.fetch_runtimes().await.unwrap().len(), 3);
+        assert_eq!(
+            proxy.requests()[0].path,
+            "http://piston.invalid/api/v2/piston/runtimes".to_string()
+        );
+    }
+
+    #[test]
+    fn test_with_invalid_proxy() {
+        assert!(matches!(
+            Client::new().with_proxy("not a url"),
+            Err(PistonError::Http(_))
+        ));
+        assert!(Client::builder().set_proxy("not a url").build().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_pool_settings() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url)
+            .with_pool_max_idle_per_host(2)
+            .with_pool_idle_timeout(Duration::from_secs(5));
+
+        assert_eq!(client.config.pool_max_idle_per_host, Some(2));
+        assert_eq!(
+            client.config.pool_idle_timeout,
+            Some(Duration::from_secs(5))
+        );
+
+        let executor = Executor::new().set_language("rust");
+        for _ in 0..3 {
+            assert!(client.execute(&executor).await.unwrap().is_ok());
+        }
+
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_https_client_with_rustls() {
+        let client = Client::builder()
+            .set_url("https://localhost:3000")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_url(), "https://localhost:3000".to_string());
+        assert!(client.config.build().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_cancellable() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_secs(2))
+        ]);
+
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+        let token = CancellationToken::new();
+
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+
+        let start = std::time::Instant::now();
+        let result = client.execute_cancellable(&executor, token).await;
+
+        assert!(matches!(result, Err(PistonError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_execute_cancellable_completes() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+
+        let result = client
+            .execute_cancellable(&executor, CancellationToken::new())
+            .await;
+
+        assert!(result.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_rate_limited() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_rate_limit(10);
+        let executors = vec![Executor::new().set_language("rust"); 4];
+
+        let start = std::time::Instant::now();
+        let results = client.execute_batch(&executors).await;
+
+        // The first request is sent immediately, the rest 100ms apart.
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_rate_limit_zero_is_unlimited() {
+        let client = Client::new().with_rate_limit(0);
+
+        assert!(client.limiter.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_max() {
+        let client = Client::new().with_rate_limit(u32::MAX);
+        let limiter = client.limiter.unwrap();
+
+        assert_eq!(limiter.period, Duration::from_nanos(1));
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_timeout() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, EXEC_BODY).with_delay(Duration::from_millis(500))
+        ]);
+
+        let client = Client::with_url(&server.url);
+        let executor = Executor::new().set_language("rust");
+        let timeout = Duration::from_millis(50);
+
+        match client.execute_with_timeout(&executor, timeout).await {
+            Err(PistonError::Http(e)) => assert!(e.is_timeout()),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_adjusted_headers() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let mut client = Client::with_url(&server.url);
+        client.set_header("X-Proxy-Auth", "letmein").unwrap();
+        assert!(client.remove_header("Accept"));
+
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_ok());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("X-Proxy-Auth"), Some("letmein"));
+        assert_ne!(requests[0].header("Accept"), Some("application/json"));
+    }
+
+    #[test]
+    fn test_headers_with_invalid_key() {
+        let client = Client::new();
+
+        assert!(client.headers_with_key("bad\nkey").is_err());
+    }
+}
+
+#[cfg(all(test, feature = "logging"))]
+mod test_client_logging {
+    use std::sync::Mutex;
+
+    use super::Client;
+    use super::Executor;
+
+    /// A logger that captures messages for inspection.
+    struct CapturingLogger {
+        messages: Mutex<Vec<String>>,
+    }
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.messages
+                .lock()
+                .unwrap()
+                .push(format!("{}", record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        messages: Mutex::new(Vec::new()),
+    };
+
+    #[tokio::test]
+    async fn test_execute_logs_endpoint() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        // Nothing listens on port 1, the request is expected to fail.
+        let client = Client::with_url_and_key("http://127.0.0.1:1", "secret-key").unwrap();
+        let executor = Executor::new().set_language("rust");
+        assert!(client.execute(&executor).await.is_err());
+
+        let messages = LOGGER.messages.lock().unwrap();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("http://127.0.0.1:1/execute")));
+        assert!(messages.iter().all(|m| !m.contains("secret-key")));
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod test_client_tracing {
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing_core::span::Current;
+
+    use super::Client;
+    use super::Executor;
+    use crate::mock::{MockResponse, MockServer};
+
+    /// A span captured by the [`CapturingSubscriber`].
+    struct CapturedSpan {
+        metadata: &'static Metadata<'static>,
+        fields: Vec<String>,
+    }
+
+    /// A subscriber that captures spans and their fields for
+    /// inspection.
+    #[derive(Clone, Default)]
+    struct CapturingSubscriber {
+        spans: Arc<Mutex<Vec<CapturedSpan>>>,
+        entered: Arc<Mutex<Vec<Id>>>,
+    }
+
+    /// Records fields as `name=value` strings.
+    struct FieldRecorder<'a>(&'a mut Vec<String>);
+
+    impl Visit for FieldRecorder<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for CapturingSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = vec![];
+            span.record(&mut FieldRecorder(&mut fields));
+
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(CapturedSpan {
+                metadata: span.metadata(),
+                fields,
+            });
+
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let span = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldRecorder(&mut span.fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            match self.entered.lock().unwrap().last() {
+                Some(id) => {
+                    let spans = self.spans.lock().unwrap();
+                    Current::new(id.clone(), spans[id.into_u64() as usize - 1].metadata)
+                }
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_emits_span() {
+        let subscriber = CapturingSubscriber::default();
+        let _guard = tracing::subscriber::set_default(subscriber.clone());
+
+        let body = r#"{"language": "rust", "version": "1.50.0", "run": {"code": 0}}"#;
+        let server = MockServer::start(vec![MockResponse::new(200, body)]);
+        let client = Client::with_url_and_key(&server.url, "secret-key").unwrap();
+        let executor = Executor::new().set_language("rust").set_version("1.50.0");
+        assert!(client.execute(&executor).await.is_ok());
+
+        let spans = subscriber.spans.lock().unwrap();
+        let span = spans
+            .iter()
+            .find(|s| s.metadata.name() == "execute")
+            .unwrap();
+        let fields = &span.fields;
+
+        let endpoint = format!("endpoint={}/execute", server.url);
+        assert!(fields.contains(&endpoint));
+        assert!(fields.contains(&"language=rust".to_string()));
+        assert!(fields.contains(&"version=1.50.0".to_string()));
+        assert!(fields.contains(&"status=200".to_string()));
+        assert!(fields.iter().any(|f| f.starts_with("duration_ms=")));
+        assert!(fields.iter().all(|f| !f.contains("secret-key")));
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod test_client_compression {
+    use std::io::Read;
+
+    use super::Client;
+    use super::Executor;
+    use crate::mock::{MockResponse, MockServer};
+
+    /// A successful execute response body.
+    const EXEC_BODY: &str = r#"{
+        "language": "rust",
+        "version": "1.50.0",
+        "run": {"stdout": "", "stderr": "", "output": "", "code": 0, "signal": null}
+    }"#;
+
+    #[tokio::test]
+    async fn test_execute_compressed() {
+        let server = MockServer::start(vec![MockResponse::new(200, EXEC_BODY)]);
+        let client = Client::with_url(&server.url).with_request_compression(true);
+        let executor = Executor::new().set_language("rust").set_stdin("42");
+
+        assert!(client.execute(&executor).await.is_ok());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("Content-Encoding"), Some("gzip"));
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(request.body.as_slice())
+            .read_to_string(&mut json)
+            .unwrap();
+
+        let expected = serde_json::to_value(&executor).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_compression_rejected() {
+        let server = MockServer::start(vec![
+            MockResponse::new(415, "Unsupported Media Type"),
+            MockResponse::new(200, EXEC_BODY),
+        ]);
+        let client = Client::with_url(&server.url).with_request_compression(true);
+        let executor = Executor::new().set_language("rust");
+
+        assert!(client.execute(&executor).await.is_ok());
+        assert!(client.execute(&executor).await.is_ok());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("Content-Encoding"), Some("gzip"));
+        assert!(requests[1].header("Content-Encoding").is_none());
+        assert!(requests[2].header("Content-Encoding").is_none());
+
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body["language"], "rust");
     }
 }
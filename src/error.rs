@@ -0,0 +1,185 @@
+use serde::Deserialize;
+
+/// The error that is returned when interacting with Piston, or the
+/// data it returns, fails for any reason.
+#[derive(Debug)]
+pub enum PistonError {
+    /// The http request to Piston failed. Contains the underlying
+    /// [`reqwest::Error`].
+    Http(reqwest::Error),
+    /// Piston responded with a non success status.
+    Api {
+        /// The status code returned by Piston.
+        status: u16,
+        /// The error message returned by Piston.
+        message: String,
+    },
+    /// The request was cancelled before Piston responded.
+    Cancelled,
+    /// The executor is not valid to send to Piston. Contains the
+    /// details of the problem.
+    InvalidExecutor(String),
+    /// A header could not be used in a request to Piston. Contains the
+    /// details of the failure.
+    InvalidHeader(String),
+    /// Data returned by Piston could not be decoded.
+    Decode {
+        /// The details of the failure.
+        details: String,
+        /// The raw response body, if a response from Piston could not
+        /// be decoded.
+        body: Option<String>,
+    },
+    /// An execution in a batch panicked. Contains the panic message.
+    Panicked(String),
+}
+
+impl std::fmt::Display for PistonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "Http request failed: {}", e),
+            Self::Api { status, message } => {
+                write!(f, "Piston returned status {}: {}", status, message)
+            }
+            Self::Cancelled => write!(f, "Request was cancelled"),
+            Self::InvalidExecutor(details) => write!(f, "Invalid executor: {}", details),
+            Self::InvalidHeader(details) => write!(f, "Invalid header: {}", details),
+            Self::Decode { details, .. } => write!(f, "Failed to decode data: {}", details),
+            Self::Panicked(message) => write!(f, "Execution panicked: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PistonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PistonError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for PistonError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode {
+            details: e.to_string(),
+            body: None,
+        }
+    }
+}
+
+impl PistonError {
+    /// Creates a [`PistonError::Decode`] for a response body that could
+    /// not be decoded, keeping the raw body.
+    ///
+    /// # Arguments
+    /// - `e` - The decoding error.
+    /// - `body` - The raw response body.
+    ///
+    /// # Returns
+    /// - [`PistonError`] - The new error.
+    pub(crate) fn decode_body(e: impl std::fmt::Display, body: &str) -> Self {
+        Self::Decode {
+            details: e.to_string(),
+            body: Some(body.to_string()),
+        }
+    }
+}
+
+/// The body Piston returns alongside a non success status.
+#[derive(Deserialize)]
+pub(crate) struct ApiError {
+    /// The error message.
+    pub message: String,
+}
+
+impl ApiError {
+    /// Extracts the error message from a response body, falling back
+    /// to the raw body if it is not a well formed error.
+    ///
+    /// # Arguments
+    /// - `body` - The response body.
+    ///
+    /// # Returns
+    /// - [`String`] - The error message.
+    pub fn message_from(body: &str) -> String {
+        match serde_json::from_str::<ApiError>(body) {
+            Ok(e) => e.message,
+            Err(_) => body.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_error_private {
+    use super::ApiError;
+    use super::PistonError;
+
+    #[test]
+    fn test_decode_display() {
+        let err = PistonError::Decode {
+            details: "bad data".to_string(),
+            body: None,
+        };
+
+        assert_eq!(format!("{}", err), "Failed to decode data: bad data");
+    }
+
+    #[test]
+    fn test_panicked_display() {
+        let err = PistonError::Panicked("grader crashed".to_string());
+
+        assert_eq!(format!("{}", err), "Execution panicked: grader crashed");
+    }
+
+    #[test]
+    fn test_api_display() {
+        let err = PistonError::Api {
+            status: 400,
+            message: "rust-1.0.0 runtime is unknown".to_string(),
+        };
+
+        assert_eq!(
+            format!("{}", err),
+            "Piston returned status 400: rust-1.0.0 runtime is unknown"
+        );
+    }
+
+    #[test]
+    fn test_from_serde_error() {
+        let e = serde_json::from_str::<Vec<String>>("[1, 2").unwrap_err();
+        let details = e.to_string();
+        let err = PistonError::from(e);
+
+        match err {
+            PistonError::Decode { details: d, body } => {
+                assert_eq!(d, details);
+                assert!(body.is_none());
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_api_error_message() {
+        let body = r#"{"message": "Requests limited to 5 per second"}"#;
+
+        assert_eq!(
+            ApiError::message_from(body),
+            "Requests limited to 5 per second".to_string()
+        );
+    }
+
+    #[test]
+    fn test_api_error_message_not_json() {
+        let body = "<html>502 Bad Gateway</html>";
+
+        assert_eq!(ApiError::message_from(body), body.to_string());
+    }
+}
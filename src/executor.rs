@@ -1,40 +1,444 @@
-use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::PathBuf;
+use std::str::Lines;
 
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::language_for_extension;
 use super::File;
+use super::LoadResult;
+use super::PistonError;
+use super::Runtime;
+use super::RuntimeSpec;
+use super::ENCODINGS;
+
+/// The run timeout sent to Piston for compile only executors. Piston
+/// treats a timeout of `0` as unset, so this is the smallest effective
+/// value.
+const COMPILE_ONLY_RUN_TIMEOUT: isize = 1;
+
+/// The fields an [`Executor`] sends to Piston, which take precedence
+/// over any extra fields with the same name.
+const PAYLOAD_FIELDS: &[&str] = &[
+    "language",
+    "version",
+    "files",
+    "stdin",
+    "args",
+    "compile_timeout",
+    "run_timeout",
+    "compile_memory_limit",
+    "run_memory_limit",
+    "binary_output",
+];
+
+/// A 64 bit FNV-1a hasher. Unlike the standard library's hasher, its
+/// output is the same across processes, versions, and platforms.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        // Lengths and isizes are widened so the hash does not depend on
+        // the platform's pointer width.
+        self.write(&(i as u64).to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
 
 /// The result of code execution returned by Piston.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Any fields missing from Piston's response take their default value,
+/// and any fields this crate does not know about are ignored, so newer
+/// versions of Piston can add fields without breaking deserialization.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExecResult {
     /// The text sent to `stdout` during execution.
     pub stdout: String,
     /// The text sent to `stderr` during execution.
     pub stderr: String,
     /// The text sent to both `stdout`, and `stderr` during execution.
+    /// Some versions of Piston do not send this, see
+    /// [`ExecResult::effective_output`].
     pub output: String,
     /// The optional exit code returned by the process.
     pub code: Option<isize>,
     /// The optional signal sent to the process. (`SIGKILL` etc)
     pub signal: Option<String>,
+    /// Whether or not `stdout` was requested as base64 encoded binary
+    /// output. See [`Executor::set_binary_output`].
+    pub binary: bool,
+    /// The optional files written by the process, if Piston is
+    /// configured to return them.
+    pub files: Option<Vec<File>>,
+    /// The optional status of the stage, sent by newer versions of
+    /// Piston. (`OL` if `stdout` was truncated, `EL` if `stderr` was
+    /// truncated, `TO` for a timeout etc)
+    pub status: Option<String>,
 }
 
 impl ExecResult {
     /// Whether or not the execution was ok.
     ///
+    /// A process that did not exit normally, for example if it was
+    /// killed by a signal, has no exit code, and is never ok.
+    ///
     /// # Returns
     /// - [`bool`] - [`true`] if the execution returned a zero exit
-    /// code.
+    ///   code.
     pub fn is_ok(&self) -> bool {
-        self.code.is_some() && self.code.unwrap() == 0
+        matches!(self.code, Some(0))
+    }
+
+    /// Whether or not the process exited successfully. An alias for
+    /// [`ExecResult::is_ok`], so a process with no exit code did not
+    /// exit successfully.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the execution returned a zero exit
+    ///   code.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     code: None,
+    ///     signal: Some("SIGKILL".to_string()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(!result.exit_success());
+    /// ```
+    pub fn exit_success(&self) -> bool {
+        self.is_ok()
     }
 
     /// Whether or not the execution produced errors.
     ///
     /// # Returns
     /// - [`bool`] - [`true`] if the execution returned a non zero exit
-    /// code.
+    ///   code.
     pub fn is_err(&self) -> bool {
         self.code.is_some() && self.code.unwrap() != 0
     }
+
+    /// Whether or not the stage exceeded its time limit. Piston kills
+    /// the process with `SIGKILL` when it times out, and newer versions
+    /// of Piston also send a `TO` status, which is preferred if sent.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the stage timed out.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     signal: Some("SIGKILL".to_string()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(result.is_timeout());
+    /// ```
+    pub fn is_timeout(&self) -> bool {
+        match self.status.as_deref() {
+            Some(status) => status == "TO",
+            None => self.signal.as_deref() == Some("SIGKILL"),
+        }
+    }
+
+    /// Whether or not the stage wrote anything to `stdout` or `stderr`,
+    /// ignoring surrounding whitespace. A successful run that produced
+    /// no output often means the program never printed anything.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if either stream is non-empty after
+    ///   trimming.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "\n".to_string(),
+    ///     code: Some(0),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(result.is_ok());
+    /// assert!(!result.produced_output());
+    /// ```
+    pub fn produced_output(&self) -> bool {
+        !self.stdout.trim().is_empty() || !self.stderr.trim().is_empty()
+    }
+
+    /// Whether or not `stdout` matches the expected output, such as
+    /// when judging a solution.
+    ///
+    /// # Arguments
+    /// - `expected` - The expected output.
+    /// - `ignore_trailing_whitespace` - Whether to compare line by
+    ///   line, ignoring whitespace at the end of each line and any
+    ///   trailing blank lines, rather than exactly.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if `stdout` matches.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "1 2 \n3\n\n".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(result.stdout_matches("1 2\n3", true));
+    /// assert!(!result.stdout_matches("1 2\n3", false));
+    /// ```
+    pub fn stdout_matches(&self, expected: &str, ignore_trailing_whitespace: bool) -> bool {
+        if !ignore_trailing_whitespace {
+            return self.stdout == expected;
+        }
+
+        fn normalize(text: &str) -> Vec<&str> {
+            let mut lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+            while lines.last() == Some(&"") {
+                lines.pop();
+            }
+
+            lines
+        }
+
+        normalize(&self.stdout) == normalize(expected)
+    }
+
+    /// An iterator over the lines sent to `stdout` during execution.
+    /// Lines end with either `\n` or `\r\n`, and a single trailing
+    /// newline does not produce an empty final line.
+    ///
+    /// # Returns
+    /// - [`Lines`] - The lines of `stdout`.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "1\n2\n".to_string(),
+    ///     stderr: String::new(),
+    ///     output: "1\n2\n".to_string(),
+    ///     code: Some(0),
+    ///     signal: None,
+    ///     binary: false,
+    ///     files: None,
+    ///     status: None,
+    /// };
+    ///
+    /// assert_eq!(result.stdout_lines().collect::<Vec<_>>(), vec!["1", "2"]);
+    /// ```
+    pub fn stdout_lines(&self) -> Lines<'_> {
+        self.stdout.lines()
+    }
+
+    /// An iterator over the lines sent to `stderr` during execution.
+    /// Lines end with either `\n` or `\r\n`, and a single trailing
+    /// newline does not produce an empty final line.
+    ///
+    /// # Returns
+    /// - [`Lines`] - The lines of `stderr`.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: String::new(),
+    ///     stderr: "error: oops\n".to_string(),
+    ///     output: "error: oops\n".to_string(),
+    ///     code: Some(1),
+    ///     signal: None,
+    ///     binary: false,
+    ///     files: None,
+    ///     status: None,
+    /// };
+    ///
+    /// assert_eq!(result.stderr_lines().collect::<Vec<_>>(), vec!["error: oops"]);
+    /// ```
+    pub fn stderr_lines(&self) -> Lines<'_> {
+        self.stderr.lines()
+    }
+
+    /// The text sent to both `stdout`, and `stderr` during execution.
+    /// Falls back to `stdout` followed by `stderr` if Piston did not
+    /// send the combined `output`.
+    ///
+    /// # Returns
+    /// - [`String`] - The combined output.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "1\n".to_string(),
+    ///     stderr: "oops\n".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(result.effective_output(), "1\noops\n".to_string());
+    /// ```
+    pub fn effective_output(&self) -> String {
+        if !self.output.is_empty() {
+            return self.output.clone();
+        }
+
+        format!("{}{}", self.stdout, self.stderr)
+    }
+
+    /// The size of `stdout` in bytes, which may differ from its number
+    /// of characters for multi-byte UTF-8 output.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of bytes in `stdout`.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "héllo".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(result.stdout_bytes(), 6);
+    /// ```
+    pub fn stdout_bytes(&self) -> usize {
+        self.stdout.len()
+    }
+
+    /// The size of `stderr` in bytes, which may differ from its number
+    /// of characters for multi-byte UTF-8 output.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of bytes in `stderr`.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stderr: "error: ✗".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(result.stderr_bytes(), 10);
+    /// ```
+    pub fn stderr_bytes(&self) -> usize {
+        self.stderr.len()
+    }
+
+    /// The size of the combined `output` in bytes, which may differ
+    /// from its number of characters for multi-byte UTF-8 output.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of bytes in `output`.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     output: "日本".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(result.output_bytes(), 6);
+    /// ```
+    pub fn output_bytes(&self) -> usize {
+        self.output.len()
+    }
+
+    /// Whether or not `stdout` or `stderr` was likely truncated by
+    /// Piston's output cap. If Piston sent a stage status, it is used
+    /// instead of the heuristic.
+    ///
+    /// # Arguments
+    /// - `limit` - The output cap of the Piston instance, in bytes.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if Piston reported truncation, or either
+    ///   stream is at least `limit` bytes long.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "a".repeat(1024),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(result.likely_truncated(1024));
+    /// assert!(!result.likely_truncated(2048));
+    /// ```
+    pub fn likely_truncated(&self, limit: usize) -> bool {
+        match self.status.as_deref() {
+            Some("OL" | "EL") => true,
+            Some(_) => false,
+            None => self.stdout.len() >= limit || self.stderr.len() >= limit,
+        }
+    }
+
+    /// The bytes sent to `stdout` during execution. If binary output
+    /// was requested, `stdout` is base64 decoded first.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<u8>, PistonError>`] - The bytes or the error, if
+    ///   `stdout` was not valid base64.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "AAEC".to_string(),
+    ///     stderr: String::new(),
+    ///     output: "AAEC".to_string(),
+    ///     code: Some(0),
+    ///     signal: None,
+    ///     binary: true,
+    ///     files: None,
+    ///     status: None,
+    /// };
+    ///
+    /// assert_eq!(result.decoded_stdout().unwrap(), vec![0, 1, 2]);
+    /// ```
+    pub fn decoded_stdout(&self) -> Result<Vec<u8>, PistonError> {
+        if !self.binary {
+            return Ok(self.stdout.as_bytes().to_vec());
+        }
+
+        base64::engine::general_purpose::STANDARD
+            .decode(self.stdout.trim_end())
+            .map_err(|e| PistonError::Decode {
+                details: e.to_string(),
+                body: None,
+            })
+    }
+}
+
+/// Deserializes a compile stage, treating an empty stage as absent.
+///
+/// # Arguments
+/// - `deserializer` - The deserializer to use.
+///
+/// # Returns
+/// - [`Result<Option<ExecResult>, D::Error>`] - The compile stage, if
+///   it was present and not empty, or the error.
+fn deserialize_compile<'de, D>(deserializer: D) -> Result<Option<ExecResult>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let stage = Option::<ExecResult>::deserialize(deserializer)?;
+    Ok(stage.filter(|s| *s != ExecResult::default()))
 }
 
 /// Raw response received from Piston
@@ -47,12 +451,49 @@ pub struct RawExecResponse {
     pub version: String,
     /// The result Piston sends detailing execution.
     pub run: ExecResult,
+    /// The results of each run, for Piston configurations that send an
+    /// array of runs alongside `run`. The Piston v2 `/execute` endpoint
+    /// only sends the single `run` object, in which case this is
+    /// [`None`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runs: Option<Vec<ExecResult>>,
     /// The optional result Piston sends detailing compilation. This
-    /// will be [`None`] for non-compiled languages.
+    /// will be [`None`] for non-compiled languages. Some Piston
+    /// instances send an empty object instead of `null` for
+    /// non-compiled languages, which is also treated as [`None`].
+    #[serde(default, deserialize_with = "deserialize_compile")]
     pub compile: Option<ExecResult>,
+    /// The maximum allowed time for compilation in milliseconds that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub compile_timeout: Option<isize>,
+    /// The maximum allowed time for execution in milliseconds that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub run_timeout: Option<isize>,
+    /// The maximum allowed memory usage for compilation in bytes that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub compile_memory_limit: Option<isize>,
+    /// The maximum allowed memory usage for execution in bytes that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub run_memory_limit: Option<isize>,
+}
+
+/// A stage of an execution in Piston.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Stage {
+    /// The compile stage, for compiled languages.
+    Compile,
+    /// The run stage.
+    Run,
 }
 
 /// A response returned by Piston when executing code.
+///
+/// Fields this crate does not know about are ignored, so newer versions
+/// of Piston can add fields without breaking deserialization.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExecResponse {
     /// The language that was used.
@@ -61,9 +502,34 @@ pub struct ExecResponse {
     pub version: String,
     /// The result Piston sends detailing execution.
     pub run: ExecResult,
+    /// The results of each run, for Piston configurations that send an
+    /// array of runs alongside `run`. The Piston v2 `/execute` endpoint
+    /// only sends the single `run` object, in which case this is
+    /// [`None`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runs: Option<Vec<ExecResult>>,
     /// The optional result Piston sends detailing compilation. This
-    /// will be [`None`] for non-compiled languages.
+    /// will be [`None`] for non-compiled languages. Some Piston
+    /// instances send an empty object instead of `null` for
+    /// non-compiled languages, which is also treated as [`None`].
+    #[serde(default, deserialize_with = "deserialize_compile")]
     pub compile: Option<ExecResult>,
+    /// The maximum allowed time for compilation in milliseconds that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub compile_timeout: Option<isize>,
+    /// The maximum allowed time for execution in milliseconds that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub run_timeout: Option<isize>,
+    /// The maximum allowed memory usage for compilation in bytes that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub compile_memory_limit: Option<isize>,
+    /// The maximum allowed memory usage for execution in bytes that
+    /// Piston applied. Only populated if Piston returns it.
+    #[serde(default)]
+    pub run_memory_limit: Option<isize>,
     /// The response status returned by Piston.
     pub status: u16,
 }
@@ -84,12 +550,190 @@ impl ExecResponse {
     pub fn is_err(&self) -> bool {
         self.status != 200
     }
+
+    /// The result of the run stage. Equivalent to the `run` field, but
+    /// makes the stage being inspected clear at the call site.
+    ///
+    /// # Returns
+    /// - [`&ExecResult`](ExecResult) - The run stage.
+    pub fn run_stage(&self) -> &ExecResult {
+        &self.run
+    }
+
+    /// Every run result Piston sent. This is `runs` if Piston sent an
+    /// array of runs, and otherwise only `run`.
+    ///
+    /// # Returns
+    /// - [`&[ExecResult]`](ExecResult) - The run results, which always
+    ///   contains at least one result.
+    ///
+    /// # Example
+    /// ```
+    /// let response = piston_rs::ExecResponse {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     run: piston_rs::ExecResult::default(),
+    ///     runs: None,
+    ///     compile: None,
+    ///     status: 200,
+    ///     compile_timeout: None,
+    ///     run_timeout: None,
+    ///     compile_memory_limit: None,
+    ///     run_memory_limit: None,
+    /// };
+    ///
+    /// assert_eq!(response.all_runs().len(), 1);
+    /// ```
+    pub fn all_runs(&self) -> &[ExecResult] {
+        match self.runs.as_deref() {
+            Some(runs) if !runs.is_empty() => runs,
+            _ => std::slice::from_ref(&self.run),
+        }
+    }
+
+    /// The result of the compile stage. Equivalent to the `compile`
+    /// field, but makes the stage being inspected clear at the call
+    /// site.
+    ///
+    /// # Returns
+    /// - [`Option<&ExecResult>`] - The compile stage, or [`None`] if
+    ///   the language is not compiled.
+    pub fn compile_stage(&self) -> Option<&ExecResult> {
+        self.compile.as_ref()
+    }
+
+    /// Whether or not the compile or run stage exceeded its time
+    /// limit. See [`ExecResult::is_timeout`].
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if either stage timed out.
+    pub fn is_timeout(&self) -> bool {
+        self.run.is_timeout() || self.compile.as_ref().is_some_and(|c| c.is_timeout())
+    }
+
+    /// Which stage exceeded its time limit, if any, so the matching
+    /// timeout can be raised with [`Executor::set_compile_timeout`] or
+    /// [`Executor::set_run_timeout`]. See [`ExecResult::is_timeout`].
+    ///
+    /// # Returns
+    /// - [`Option<Stage>`] - The stage that timed out, or [`None`] if
+    ///   neither did.
+    ///
+    /// # Example
+    /// ```
+    /// let response = piston_rs::ExecResponse {
+    ///     language: "rust".to_string(),
+    ///     version: "1.50.0".to_string(),
+    ///     run: piston_rs::ExecResult::default(),
+    ///     runs: None,
+    ///     compile: Some(piston_rs::ExecResult {
+    ///         signal: Some("SIGKILL".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     status: 200,
+    ///     compile_timeout: None,
+    ///     run_timeout: None,
+    ///     compile_memory_limit: None,
+    ///     run_memory_limit: None,
+    /// };
+    ///
+    /// assert_eq!(response.which_stage_timed_out(), Some(piston_rs::Stage::Compile));
+    /// ```
+    pub fn which_stage_timed_out(&self) -> Option<Stage> {
+        if self.compile.as_ref().is_some_and(|c| c.is_timeout()) {
+            Some(Stage::Compile)
+        } else if self.run.is_timeout() {
+            Some(Stage::Run)
+        } else {
+            None
+        }
+    }
+
+    /// The exit code returned by the process in the run stage.
+    ///
+    /// # Returns
+    /// - [`Option<isize>`] - The exit code, or [`None`] if the process
+    ///   did not exit normally, for example if it was killed.
+    pub fn exit_code(&self) -> Option<isize> {
+        self.run.code
+    }
+
+    /// The files written by the process in the run stage, if Piston
+    /// returned any.
+    ///
+    /// # Returns
+    /// - [`&[File]`](File) - The output files, which is empty if Piston did
+    ///   not return any.
+    pub fn output_files(&self) -> &[File] {
+        self.run.files.as_deref().unwrap_or_default()
+    }
+
+    /// Whether or not the compile stage succeeded. Useful alongside
+    /// [`Executor::set_compile_only`].
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if there was a compile stage, and it
+    ///   returned a zero exit code.
+    pub fn compiled_successfully(&self) -> bool {
+        self.compile.as_ref().is_some_and(|c| c.is_ok())
+    }
+
+    /// Converts the response into a [`Result`], so failed executions
+    /// can be propagated with `?`.
+    ///
+    /// The execution succeeded if Piston responded with a 200 status,
+    /// the compile stage, if any, returned a zero exit code, and the
+    /// run stage returned a zero exit code. A process killed by a
+    /// signal, such as on timeout, has no exit code and so failed.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, Box<ExecResponse>>`] - [`Ok`] with the
+    ///   response if the execution succeeded, or [`Err`] with the boxed
+    ///   response otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// let response = piston_rs::ExecResponse {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     run: piston_rs::ExecResult {
+    ///         stderr: "NameError: name 'x' is not defined\n".to_string(),
+    ///         code: Some(1),
+    ///         ..Default::default()
+    ///     },
+    ///     runs: None,
+    ///     compile: None,
+    ///     status: 200,
+    ///     compile_timeout: None,
+    ///     run_timeout: None,
+    ///     compile_memory_limit: None,
+    ///     run_memory_limit: None,
+    /// };
+    ///
+    /// let failed = response.into_result().unwrap_err();
+    /// assert_eq!(failed.exit_code(), Some(1));
+    /// ```
+    pub fn into_result(self) -> Result<ExecResponse, Box<ExecResponse>> {
+        let compiled = match &self.compile {
+            Some(compile) => compile.is_ok(),
+            None => true,
+        };
+
+        if self.is_ok() && compiled && self.run.is_ok() {
+            Ok(self)
+        } else {
+            Err(Box::new(self))
+        }
+    }
 }
 
 /// An object containing information about the code being executed.
 ///
 /// A convenient builder flow is provided by the methods associated with
 /// the `Executor`. These consume self and return self for chained calls.
+///
+/// Executors, and the responses Piston returns, are [`Send`] and
+/// [`Sync`], so they can be shared between threads.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Executor {
     /// **Required** - The language to use for execution. Defaults to a
@@ -109,10 +753,12 @@ pub struct Executor {
     /// `Vector`.
     pub args: Vec<String>,
     /// The maximum allowed time for compilation in milliseconds.
-    /// Defaults to `10,000`.
+    /// Defaults to `10,000`. `-1` disables the timeout on instances
+    /// that allow it.
     pub compile_timeout: isize,
     /// The maximum allowed time for execution in milliseconds. Defaults
-    /// to `3,000`.
+    /// to `3,000`. `-1` disables the timeout on instances that allow
+    /// it.
     pub run_timeout: isize,
     /// The maximum allowed memory usage for compilation in bytes.
     /// Defaults to `-1` (*no limit*).
@@ -120,6 +766,28 @@ pub struct Executor {
     /// The maximum allowed memory usage for execution in bytes.
     /// Defaults to `-1` (*no limit*).
     pub run_memory_limit: isize,
+    /// Whether or not to request `stdout` as base64 encoded binary
+    /// output, if the Piston instance supports it. Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub binary_output: bool,
+    /// Whether or not to only compile the code. This is a best effort
+    /// approximation, as Piston always runs code after compiling it.
+    /// Instead, the smallest possible run timeout is sent. Defaults to
+    /// `false`.
+    #[serde(skip)]
+    pub compile_only: bool,
+    /// The interpreter to run the main file with, such as
+    /// `/usr/bin/env bash`. Piston has no entrypoint option, so this is
+    /// sent as a shebang line prepended to the main file, replacing any
+    /// shebang it already has. Defaults to [`None`].
+    #[serde(skip)]
+    pub entrypoint: Option<String>,
+    /// Extra fields to send in the request body, for options newer
+    /// Piston versions support that this crate does not yet. Fields
+    /// the executor already sends take precedence over these. Defaults
+    /// to a new `HashMap`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 impl Default for Executor {
@@ -140,6 +808,36 @@ impl Default for Executor {
     }
 }
 
+impl std::fmt::Display for Executor {
+    /// Summarizes the executor on one line, for logging.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default())
+    ///     .add_arg("--verbose");
+    ///
+    /// assert_eq!(
+    ///     executor.to_string(),
+    ///     "rust * (files: 1, stdin: no, args: yes)".to_string(),
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let yes_no = |set: bool| if set { "yes" } else { "no" };
+
+        write!(
+            f,
+            "{} {} (files: {}, stdin: {}, args: {})",
+            self.language,
+            self.version,
+            self.files.len(),
+            yes_no(!self.stdin.is_empty()),
+            yes_no(!self.args.is_empty()),
+        )
+    }
+}
+
 impl Executor {
     /// Creates a new executor representing source code to be
     /// executed.
@@ -169,9 +867,32 @@ impl Executor {
             run_timeout: 3000,
             compile_memory_limit: -1,
             run_memory_limit: -1,
+            binary_output: false,
+            compile_only: false,
+            entrypoint: None,
+            extra: HashMap::new(),
         }
     }
 
+    /// Creates a new [`ExecutorBuilder`], which validates the required
+    /// fields when the executor is built.
+    ///
+    /// # Returns
+    /// - [`ExecutorBuilder`] - The new blank ExecutorBuilder.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::builder()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default())
+    ///     .build();
+    ///
+    /// assert!(executor.is_ok());
+    /// ```
+    pub fn builder() -> ExecutorBuilder {
+        ExecutorBuilder::new()
+    }
+
     /// Resets the executor back to a `new` state, ready to be
     /// configured again and sent to Piston after metadata is added.
     /// This method mutates the existing executor in place.
@@ -197,6 +918,10 @@ impl Executor {
         self.run_timeout = 3000;
         self.compile_memory_limit = -1;
         self.run_memory_limit = -1;
+        self.binary_output = false;
+        self.compile_only = false;
+        self.entrypoint = None;
+        self.extra = HashMap::new();
     }
 
     /// Sets the language to use for execution.
@@ -241,117 +966,232 @@ impl Executor {
         self
     }
 
-    /// Adds a [`File`] containing the code to be executed. Does not
-    /// overwrite any existing files.
+    /// Sets the language and version to use for execution from a
+    /// [`Runtime`] fetched from Piston.
     ///
     /// # Arguments
-    /// - `file` - The file to add.
+    /// - `runtime` - The runtime to use.
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
-    /// let file = piston_rs::File::default();
+    /// let runtime = piston_rs::Runtime {
+    ///     language: "rust".to_string(),
+    ///     version: "1.50.0".to_string(),
+    ///     aliases: vec!["rs".to_string()],
+    /// };
     ///
     /// let executor = piston_rs::Executor::new()
-    ///     .add_file(file.clone());
+    ///     .use_runtime(&runtime);
     ///
-    /// assert_eq!(executor.files, [file].to_vec());
+    /// assert_eq!(executor.language, "rust".to_string());
+    /// assert_eq!(executor.version, "1.50.0".to_string());
     /// ```
     #[must_use]
-    pub fn add_file(mut self, file: File) -> Self {
-        self.files.push(file);
-        self
+    pub fn use_runtime(self, runtime: &Runtime) -> Self {
+        self.set_language(&runtime.language)
+            .set_version(&runtime.version)
     }
 
-    /// Adds multiple [`File`]'s containing the code to be executed.
-    /// Does not overwrite any existing files.
+    /// Sets the language and version to use for execution from a
+    /// [`RuntimeSpec`], such as one parsed from user input.
     ///
     /// # Arguments
-    /// - `files` - The files to add.
+    /// - `spec` - The spec to use.
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
-    /// let mut files = vec![];
-    ///
-    /// for _ in 0..3 {
-    ///     files.push(piston_rs::File::default());
-    /// }
-    ///
+    /// let spec: piston_rs::RuntimeSpec = "python@3.10".parse().unwrap();
     /// let executor = piston_rs::Executor::new()
-    ///     .add_files(files.clone());
+    ///     .use_spec(&spec);
     ///
-    /// assert_eq!(executor.files, files);
+    /// assert_eq!(executor.language, "python".to_string());
+    /// assert_eq!(executor.version, "3.10".to_string());
     /// ```
     #[must_use]
-    pub fn add_files(mut self, files: Vec<File>) -> Self {
-        self.files.extend(files);
-        self
+    pub fn use_spec(self, spec: &RuntimeSpec) -> Self {
+        self.set_language(&spec.language).set_version(&spec.version)
     }
 
-    /// Adds multiple [`File`]'s containing the code to be executed.
-    /// Overwrites any existing files. This method mutates the existing
-    /// executor in place. **Overwrites any existing files.**
+    /// Sets the language from the extension of the main [`File`], if
+    /// the language has not been set. See
+    /// [`language_for_extension`](crate::language_for_extension). If
+    /// the extension is not recognized, the language is left empty.
     ///
-    /// # Arguments
-    /// - `files` - The files to replace existing files with.
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
-    /// let old_file = piston_rs::File::default()
-    ///     .set_name("old_file.rs");
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .infer_language();
     ///
-    /// let mut executor = piston_rs::Executor::new()
-    ///     .add_file(old_file.clone());
+    /// assert_eq!(executor.language, "rust".to_string());
+    /// ```
+    #[must_use]
+    pub fn infer_language(mut self) -> Self {
+        if !self.language.is_empty() {
+            return self;
+        }
+
+        let language = self
+            .main_file()
+            .and_then(|f| f.extension())
+            .and_then(language_for_extension);
+
+        if let Some(language) = language {
+            self.language = language.to_string();
+        }
+
+        self
+    }
+
+    /// Creates a copy of this executor with a different language and
+    /// version, keeping the files, stdin, args, and limits intact.
+    /// Useful for running the same input against several languages.
     ///
-    /// assert_eq!(executor.files.len(), 1);
-    /// assert_eq!(executor.files[0].name, "old_file.rs".to_string());
+    /// # Arguments
+    /// - `language` - The language to use in the copy.
+    /// - `version` - The version to use in the copy.
     ///
-    /// let new_files = vec![
-    ///     piston_rs::File::default().set_name("new_file1.rs"),
-    ///     piston_rs::File::default().set_name("new_file2.rs"),
-    /// ];
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
     ///
-    /// executor.set_files(new_files.clone());
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .set_version("3.10.0")
+    ///     .set_stdin("42");
     ///
-    /// assert_eq!(executor.files.len(), 2);
-    /// assert_eq!(executor.files[0].name, "new_file1.rs".to_string());
-    /// assert_eq!(executor.files[1].name, "new_file2.rs".to_string());
+    /// let other = executor.with_language_version("ruby", "3.0.1");
+    ///
+    /// assert_eq!(other.language, "ruby".to_string());
+    /// assert_eq!(other.version, "3.0.1".to_string());
+    /// assert_eq!(other.stdin, executor.stdin);
     /// ```
-    pub fn set_files(&mut self, files: Vec<File>) {
-        self.files = files;
+    #[must_use]
+    pub fn with_language_version(&self, language: &str, version: &str) -> Executor {
+        self.clone().set_language(language).set_version(version)
     }
 
-    /// Sets the text to pass as `stdin` to the program.
+    /// Combines another executor into this one, such as user supplied
+    /// input into a template.
+    ///
+    /// `other`'s files and args are appended after this executor's.
+    /// Every other field of `other` overrides this executor's, but only
+    /// if it differs from the default of [`Executor::new`]. So a
+    /// template's settings are kept unless `other` changes them.
     ///
     /// # Arguments
-    /// - `stdin` - The text to set.
+    /// - `other` - The executor to merge in.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let template = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .set_version("3.10.0")
+    ///     .add_file(piston_rs::File::default().set_name("harness.py"));
+    ///
+    /// let user = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("solution.py"))
+    ///     .set_stdin("42");
+    ///
+    /// let executor = template.merge(user);
+    ///
+    /// assert_eq!(executor.language, "python".to_string());
+    /// assert_eq!(executor.files[1].name, "solution.py".to_string());
+    /// assert_eq!(executor.stdin, "42".to_string());
+    /// ```
+    #[must_use]
+    pub fn merge(mut self, other: Executor) -> Self {
+        let default = Executor::new();
+
+        if other.language != default.language {
+            self.language = other.language;
+        }
+
+        if other.version != default.version {
+            self.version = other.version;
+        }
+
+        if other.stdin != default.stdin {
+            self.stdin = other.stdin;
+        }
+
+        if other.compile_timeout != default.compile_timeout {
+            self.compile_timeout = other.compile_timeout;
+        }
+
+        if other.run_timeout != default.run_timeout {
+            self.run_timeout = other.run_timeout;
+        }
+
+        if other.compile_memory_limit != default.compile_memory_limit {
+            self.compile_memory_limit = other.compile_memory_limit;
+        }
+
+        if other.run_memory_limit != default.run_memory_limit {
+            self.run_memory_limit = other.run_memory_limit;
+        }
+
+        if other.binary_output != default.binary_output {
+            self.binary_output = other.binary_output;
+        }
+
+        if other.compile_only != default.compile_only {
+            self.compile_only = other.compile_only;
+        }
+
+        if other.entrypoint != default.entrypoint {
+            self.entrypoint = other.entrypoint;
+        }
+
+        self.files.extend(other.files);
+        self.args.extend(other.args);
+        self.extra.extend(other.extra);
+        self
+    }
+
+    /// Adds a [`File`] containing the code to be executed. Does not
+    /// overwrite any existing files.
+    ///
+    /// # Arguments
+    /// - `file` - The file to add.
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
+    /// let file = piston_rs::File::default();
+    ///
     /// let executor = piston_rs::Executor::new()
-    ///     .set_stdin("Fearless concurrency");
+    ///     .add_file(file.clone());
     ///
-    /// assert_eq!(executor.stdin, "Fearless concurrency".to_string());
+    /// assert_eq!(executor.files, [file].to_vec());
     /// ```
     #[must_use]
-    pub fn set_stdin(mut self, stdin: &str) -> Self {
-        self.stdin = stdin.to_string();
+    pub fn add_file(mut self, file: File) -> Self {
+        self.files.push(file);
         self
     }
 
-    /// Adds an arg to be passed as a command line argument. Does not
-    /// overwrite any existing args.
+    /// Adds a [`File`] containing the code to be executed in front of
+    /// any existing files, making it the main file. Does not overwrite
+    /// any existing files.
     ///
     /// # Arguments
-    /// - `arg` - The arg to add.
+    /// - `file` - The file to add.
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
@@ -359,107 +1199,166 @@ impl Executor {
     /// # Example
     /// ```
     /// let executor = piston_rs::Executor::new()
-    ///     .add_arg("--verbose");
+    ///     .add_file(piston_rs::File::default().set_name("helper.py"))
+    ///     .prepend_file(piston_rs::File::default().set_name("main.py"));
     ///
-    /// assert_eq!(executor.args, vec!["--verbose".to_string()]);
+    /// assert_eq!(executor.files[0].name, "main.py".to_string());
+    /// assert_eq!(executor.files[1].name, "helper.py".to_string());
     /// ```
     #[must_use]
-    pub fn add_arg(mut self, arg: &str) -> Self {
-        self.args.push(arg.to_string());
+    pub fn prepend_file(mut self, file: File) -> Self {
+        self.files.insert(0, file);
         self
     }
 
-    /// Adds multiple args to be passed as a command line arguments.
-    /// Does not overwrite any existing args.
+    /// Adds multiple [`File`]'s containing the code to be executed.
+    /// Does not overwrite any existing files.
     ///
     /// # Arguments
-    /// - `args` - The args to add.
+    /// - `files` - The files to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
     ///
     /// # Example
     /// ```
+    /// let mut files = vec![];
+    ///
+    /// for _ in 0..3 {
+    ///     files.push(piston_rs::File::default());
+    /// }
+    ///
     /// let executor = piston_rs::Executor::new()
-    ///     .add_args(vec!["--verbose"]);
+    ///     .add_files(files.clone());
     ///
-    /// assert_eq!(executor.args, vec!["--verbose".to_string()]);
+    /// assert_eq!(executor.files, files);
     /// ```
     #[must_use]
-    pub fn add_args(mut self, args: Vec<&str>) -> Self {
-        self.args.extend(args.iter().map(|a| a.to_string()));
+    pub fn add_files(mut self, files: Vec<File>) -> Self {
+        self.files.extend(files);
         self
     }
 
-    /// Adds multiple args to be passed as a command line arguments.
-    /// Overwrites any existing args. This method mutates the existing
-    /// executor in place. **Overwrites any existing args.**
+    /// Adds multiple [`File`]'s containing the code to be executed.
+    /// Overwrites any existing files. This method mutates the existing
+    /// executor in place. **Overwrites any existing files.**
     ///
     /// # Arguments
-    /// - `args` - The args to replace existing args with.
+    /// - `files` - The files to replace existing files with.
     ///
     /// # Example
     /// ```
+    /// let old_file = piston_rs::File::default()
+    ///     .set_name("old_file.rs");
+    ///
     /// let mut executor = piston_rs::Executor::new()
-    ///     .add_arg("--verbose");
+    ///     .add_file(old_file.clone());
     ///
-    /// assert_eq!(executor.args.len(), 1);
-    /// assert_eq!(executor.args[0], "--verbose".to_string());
+    /// assert_eq!(executor.files.len(), 1);
+    /// assert_eq!(executor.files[0].name, "old_file.rs".to_string());
     ///
-    /// let args = vec!["commit", "-S"];
-    /// executor.set_args(args);
+    /// let new_files = vec![
+    ///     piston_rs::File::default().set_name("new_file1.rs"),
+    ///     piston_rs::File::default().set_name("new_file2.rs"),
+    /// ];
     ///
-    /// assert_eq!(executor.args.len(), 2);
-    /// assert_eq!(executor.args[0], "commit".to_string());
-    /// assert_eq!(executor.args[1], "-S".to_string());
+    /// executor.set_files(new_files.clone());
+    ///
+    /// assert_eq!(executor.files.len(), 2);
+    /// assert_eq!(executor.files[0].name, "new_file1.rs".to_string());
+    /// assert_eq!(executor.files[1].name, "new_file2.rs".to_string());
     /// ```
-    pub fn set_args(&mut self, args: Vec<&str>) {
-        self.args = args.iter().map(|a| a.to_string()).collect();
+    pub fn set_files(&mut self, files: Vec<File>) {
+        self.files = files;
     }
 
-    /// Sets the maximum allowed time for compilation in milliseconds.
+    /// The main [`File`] of the executor. Piston considers the first
+    /// file to be the main file.
+    ///
+    /// # Returns
+    /// - [`Option<&File>`] - The main file, or [`None`] if no files
+    ///   have been added.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new();
+    ///
+    /// assert!(executor.main_file().is_none());
+    ///
+    /// let executor = executor
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs"));
+    ///
+    /// assert_eq!(executor.main_file().unwrap().name, "main.rs".to_string());
+    /// ```
+    pub fn main_file(&self) -> Option<&File> {
+        self.files.first()
+    }
+
+    /// Makes the file with the given name the main [`File`], by moving
+    /// it to the front. The order of the other files is kept. This
+    /// method mutates the existing executor in place.
     ///
     /// # Arguments
-    /// - `timeout` - The timeout to set.
+    /// - `name` - The name of the file.
     ///
     /// # Returns
-    /// - [`Self`] - For chained method calls.
+    /// - [`bool`] - [`true`] if a file with the name was found.
     ///
     /// # Example
     /// ```
-    /// let executor = piston_rs::Executor::new()
-    ///     .set_compile_timeout(5000);
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"));
     ///
-    /// assert_eq!(executor.compile_timeout, 5000);
+    /// assert!(executor.set_main_file("main.rs"));
+    /// assert_eq!(executor.main_file().unwrap().name, "main.rs".to_string());
     /// ```
-    #[must_use]
-    pub fn set_compile_timeout(mut self, timeout: isize) -> Self {
-        self.compile_timeout = timeout;
-        self
+    pub fn set_main_file(&mut self, name: &str) -> bool {
+        match self.files.iter().position(|f| f.name == name) {
+            Some(i) => {
+                let file = self.files.remove(i);
+                self.files.insert(0, file);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Sets the maximum allowed time for execution in milliseconds.
+    /// Removes the first [`File`] with the given name. This method
+    /// mutates the existing executor in place.
     ///
     /// # Arguments
-    /// - `timeout` - The timeout to set.
+    /// - `name` - The name of the file.
     ///
     /// # Returns
-    /// - [`Self`] - For chained method calls.
+    /// - [`bool`] - [`true`] if a file with the name was removed.
     ///
     /// # Example
     /// ```
-    /// let executor = piston_rs::Executor::new()
-    ///     .set_run_timeout(1500);
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs"));
     ///
-    /// assert_eq!(executor.run_timeout, 1500);
+    /// assert!(executor.remove_file("lib.rs"));
+    /// assert_eq!(executor.file_count(), 1);
     /// ```
-    #[must_use]
-    pub fn set_run_timeout(mut self, timeout: isize) -> Self {
-        self.run_timeout = timeout;
-        self
+    pub fn remove_file(&mut self, name: &str) -> bool {
+        match self.files.iter().position(|f| f.name == name) {
+            Some(i) => {
+                self.files.remove(i);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Sets the maximum allowed memory usage for compilation in bytes.
+    /// Removes the first [`File`] with the given name, if any. Unlike
+    /// [`Executor::remove_file`], this consumes the executor for
+    /// chained method calls.
     ///
     /// # Arguments
-    /// - `limit` - The memory limit to set.
+    /// - `name` - The name of the file.
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
@@ -467,102 +1366,2488 @@ impl Executor {
     /// # Example
     /// ```
     /// let executor = piston_rs::Executor::new()
-    ///     .set_compile_memory_limit(100_000_000);
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs"))
+    ///     .without_file("lib.rs");
     ///
-    /// assert_eq!(executor.compile_memory_limit, 100_000_000);
+    /// assert_eq!(executor.file_count(), 1);
+    /// assert_eq!(executor.files[0].name, "main.rs".to_string());
     /// ```
     #[must_use]
-    pub fn set_compile_memory_limit(mut self, limit: isize) -> Self {
-        self.compile_memory_limit = limit;
+    pub fn without_file(mut self, name: &str) -> Self {
+        self.remove_file(name);
         self
     }
 
-    /// Sets the maximum allowed memory usage for execution in bytes.
+    /// The first [`File`] with the given name, if any.
     ///
     /// # Arguments
-    /// - `limit` - The memory limit to set.
+    /// - `name` - The name of the file.
     ///
     /// # Returns
-    /// - [`Self`] - For chained method calls.
+    /// - [`Option<&File>`] - The file, or [`None`] if no file has the
+    ///   name.
     ///
     /// # Example
     /// ```
     /// let executor = piston_rs::Executor::new()
-    ///     .set_run_memory_limit(100_000_000);
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs").set_content("pub fn f() {}"));
     ///
-    /// assert_eq!(executor.run_memory_limit, 100_000_000);
+    /// assert_eq!(executor.file_by_name("lib.rs").unwrap().content, "pub fn f() {}".to_string());
+    /// assert!(executor.file_by_name("missing.rs").is_none());
     /// ```
-    #[must_use]
-    pub fn set_run_memory_limit(mut self, limit: isize) -> Self {
-        self.run_memory_limit = limit;
-        self
+    pub fn file_by_name(&self, name: &str) -> Option<&File> {
+        self.files.iter().find(|f| f.name == name)
     }
-}
 
-#[cfg(test)]
-mod test_execution_result {
-    use super::ExecResponse;
-    use super::ExecResult;
+    /// The first [`File`] with the given name, if any, for editing it
+    /// in place.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the file.
+    ///
+    /// # Returns
+    /// - [`Option<&mut File>`] - The file, or [`None`] if no file has
+    ///   the name.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"));
+    ///
+    /// if let Some(file) = executor.file_by_name_mut("main.rs") {
+    ///     file.content = "fn main() {}".to_string();
+    /// }
+    ///
+    /// assert_eq!(executor.files[0].content, "fn main() {}".to_string());
+    /// ```
+    pub fn file_by_name_mut(&mut self, name: &str) -> Option<&mut File> {
+        self.files.iter_mut().find(|f| f.name == name)
+    }
 
-    /// Generates an ExecResult for testing
-    fn generate_result(stdout: &str, stderr: &str, code: isize) -> ExecResult {
-        ExecResult {
-            stdout: stdout.to_string(),
-            stderr: stderr.to_string(),
-            output: format!("{}\n{}", stdout, stderr),
-            code: Some(code),
-            signal: None,
-        }
+    /// Rearranges the files to follow the given order of names. Files
+    /// not named in the order keep their relative position, after the
+    /// named files. This method mutates the existing executor in place.
+    ///
+    /// # Arguments
+    /// - `order` - The names of the files, in order.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("util.c"))
+    ///     .add_file(piston_rs::File::default().set_name("main.c"));
+    ///
+    /// executor.reorder_files(&["main.c"]);
+    /// assert_eq!(executor.files[0].name, "main.c".to_string());
+    /// assert_eq!(executor.files[1].name, "util.c".to_string());
+    /// ```
+    pub fn reorder_files(&mut self, order: &[&str]) {
+        self.files.sort_by_key(|f| {
+            order
+                .iter()
+                .position(|name| *name == f.name)
+                .unwrap_or(order.len())
+        });
     }
 
-    /// Generates an ExecResponse for testing.
-    fn generate_response(status: u16) -> ExecResponse {
-        ExecResponse {
-            language: "rust".to_string(),
-            version: "1.50.0".to_string(),
-            run: generate_result("Be unique.", "", 0),
-            compile: None,
-            status,
+    /// Appends to the content of the main [`File`], creating a default
+    /// file if none have been added. This method mutates the existing
+    /// executor in place.
+    ///
+    /// # Arguments
+    /// - `extra` - The content to append.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_content("x = 41\n"));
+    ///
+    /// executor.append_to_main("print(x + 1)\n");
+    ///
+    /// assert_eq!(executor.files[0].content, "x = 41\nprint(x + 1)\n".to_string());
+    /// ```
+    pub fn append_to_main(&mut self, extra: &str) {
+        if self.files.is_empty() {
+            self.files.push(File::default());
         }
+
+        self.files[0].content.push_str(extra);
     }
 
-    #[test]
+    /// The number of files in the executor.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of files.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_files(vec![piston_rs::File::default(), piston_rs::File::default()]);
+    ///
+    /// assert_eq!(executor.file_count(), 2);
+    /// ```
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// The total number of lines across all files in the executor.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of lines.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_content("a\nb"))
+    ///     .add_file(piston_rs::File::default().set_content("c"));
+    ///
+    /// assert_eq!(executor.total_lines(), 3);
+    /// ```
+    pub fn total_lines(&self) -> usize {
+        self.files.iter().map(|f| f.line_count()).sum()
+    }
+
+    /// Sets the encoding of every file in the executor. This method
+    /// mutates the existing executor in place.
+    ///
+    /// # Arguments
+    /// - `encoding` - The encoding to use. Must be one of "utf8",
+    ///   "hex", or "base64".
+    ///
+    /// # Returns
+    /// - [`Result<(), PistonError>`] - The error, if the encoding is
+    ///   not one of the allowed encodings.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_files(vec![piston_rs::File::default(); 3]);
+    ///
+    /// assert!(executor.set_encoding("base64").is_ok());
+    /// assert!(executor.files.iter().all(|f| f.encoding == "base64"));
+    ///
+    /// assert!(executor.set_encoding("latin1").is_err());
+    /// assert!(executor.files.iter().all(|f| f.encoding == "base64"));
+    /// ```
+    pub fn set_encoding(&mut self, encoding: &str) -> Result<(), PistonError> {
+        if !ENCODINGS.contains(&encoding) {
+            return Err(PistonError::InvalidExecutor(format!(
+                "Invalid encoding '{}', must be one of: {}",
+                encoding,
+                ENCODINGS.join(", ")
+            )));
+        }
+
+        for file in &mut self.files {
+            file.encoding = encoding.to_string();
+        }
+
+        Ok(())
+    }
+
+    /// Sets the text to pass as `stdin` to the program.
+    ///
+    /// # Arguments
+    /// - `stdin` - The text to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_stdin("Fearless concurrency");
+    ///
+    /// assert_eq!(executor.stdin, "Fearless concurrency".to_string());
+    /// ```
+    #[must_use]
+    pub fn set_stdin(mut self, stdin: &str) -> Self {
+        self.stdin = stdin.to_string();
+        self
+    }
+
+    /// Sets the lines to pass as `stdin` to the program, joined with
+    /// `\n` and followed by a trailing newline.
+    ///
+    /// # Arguments
+    /// - `lines` - The lines to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_stdin_lines(&["3", "1 2 3"]);
+    ///
+    /// assert_eq!(executor.stdin, "3\n1 2 3\n".to_string());
+    /// ```
+    #[must_use]
+    pub fn set_stdin_lines(mut self, lines: &[&str]) -> Self {
+        self.stdin = lines.iter().map(|l| format!("{}\n", l)).collect();
+        self
+    }
+
+    /// The size of `stdin` in bytes.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of bytes.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_stdin("héllo");
+    ///
+    /// assert_eq!(executor.stdin_bytes(), 6);
+    /// ```
+    pub fn stdin_bytes(&self) -> usize {
+        self.stdin.len()
+    }
+
+    /// Sets the text to pass as `stdin` to the program to the contents
+    /// of an existing file on disk.
+    ///
+    /// # Arguments
+    /// - `path` - The path to the file.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .load_stdin_from("src/executor.rs");
+    ///
+    /// assert!(executor.is_ok());
+    /// assert!(executor.unwrap().stdin.contains("pub fn load_stdin_from"));
+    ///
+    /// let executor = piston_rs::Executor::new()
+    ///     .load_stdin_from("/path/doesnt/exist");
+    ///
+    /// assert!(executor.is_err());
+    /// ```
+    pub fn load_stdin_from(mut self, path: &str) -> LoadResult<Self> {
+        let path = PathBuf::from(path);
+        self.stdin = File::load_contents(&path)?;
+        Ok(self)
+    }
+
+    /// Sets the text to pass as `stdin` to the program to everything
+    /// read from the given reader, which must be valid UTF-8.
+    ///
+    /// # Arguments
+    /// - `reader` - The reader to read `stdin` from.
+    ///
+    /// # Returns
+    /// - [`std::io::Result<Self>`] - For chained method calls, or the
+    ///   error if reading failed or the data was not valid UTF-8.
+    ///
+    /// # Example
+    /// ```
+    /// let reader = std::io::Cursor::new(b"1\n2\n".to_vec());
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_stdin_from_reader(reader)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(executor.stdin, "1\n2\n".to_string());
+    /// ```
+    pub fn set_stdin_from_reader<R: Read>(mut self, mut reader: R) -> std::io::Result<Self> {
+        let mut stdin = String::new();
+        reader.read_to_string(&mut stdin)?;
+        self.stdin = stdin;
+        Ok(self)
+    }
+
+    /// Adds an arg to be passed as a command line argument. Does not
+    /// overwrite any existing args.
+    ///
+    /// # Arguments
+    /// - `arg` - The arg to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_arg("--verbose");
+    ///
+    /// assert_eq!(executor.args, vec!["--verbose".to_string()]);
+    /// ```
+    #[must_use]
+    pub fn add_arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// Adds multiple args to be passed as a command line arguments.
+    /// Does not overwrite any existing args.
+    ///
+    /// # Arguments
+    /// - `args` - The args to add.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_args(vec!["--verbose"]);
+    ///
+    /// assert_eq!(executor.args, vec!["--verbose".to_string()]);
+    /// ```
+    #[must_use]
+    pub fn add_args(mut self, args: Vec<&str>) -> Self {
+        self.args.extend(args.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Adds multiple args to be passed as a command line arguments.
+    /// Overwrites any existing args. This method mutates the existing
+    /// executor in place. **Overwrites any existing args.**
+    ///
+    /// # Arguments
+    /// - `args` - The args to replace existing args with.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_arg("--verbose");
+    ///
+    /// assert_eq!(executor.args.len(), 1);
+    /// assert_eq!(executor.args[0], "--verbose".to_string());
+    ///
+    /// let args = vec!["commit", "-S"];
+    /// executor.set_args(args);
+    ///
+    /// assert_eq!(executor.args.len(), 2);
+    /// assert_eq!(executor.args[0], "commit".to_string());
+    /// assert_eq!(executor.args[1], "-S".to_string());
+    /// ```
+    pub fn set_args(&mut self, args: Vec<&str>) {
+        self.args = args.iter().map(|a| a.to_string()).collect();
+    }
+
+    /// Adds multiple owned args to be passed as command line
+    /// arguments. Does not overwrite any existing args.
+    ///
+    /// # Arguments
+    /// - `args` - The args to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let input = vec!["--verbose".to_string()];
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_args_owned(input);
+    ///
+    /// assert_eq!(executor.args, vec!["--verbose".to_string()]);
+    /// ```
+    #[must_use]
+    pub fn add_args_owned(mut self, args: Vec<String>) -> Self {
+        self.args.extend(args);
+        self
+    }
+
+    /// Sets the owned args to be passed as command line arguments.
+    /// This method mutates the existing executor in place.
+    /// **Overwrites any existing args.**
+    ///
+    /// # Arguments
+    /// - `args` - The args to replace existing args with.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_arg("--verbose");
+    ///
+    /// executor.set_args_owned(vec!["commit".to_string(), "-S".to_string()]);
+    ///
+    /// assert_eq!(executor.args, vec!["commit".to_string(), "-S".to_string()]);
+    /// ```
+    pub fn set_args_owned(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Removes the first arg equal to the given arg. This method
+    /// mutates the existing executor in place.
+    ///
+    /// # Arguments
+    /// - `arg` - The arg to remove.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if a matching arg was removed.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_args(vec!["--verbose", "--fast"]);
+    ///
+    /// assert!(executor.remove_arg("--verbose"));
+    /// assert_eq!(executor.args, vec!["--fast".to_string()]);
+    /// ```
+    pub fn remove_arg(&mut self, arg: &str) -> bool {
+        match self.args.iter().position(|a| a == arg) {
+            Some(i) => {
+                self.args.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all args. This method mutates the existing executor in
+    /// place.
+    ///
+    /// # Example
+    /// ```
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .add_args(vec!["--verbose", "--fast"]);
+    ///
+    /// executor.clear_args();
+    /// assert!(executor.args.is_empty());
+    /// ```
+    pub fn clear_args(&mut self) {
+        self.args.clear();
+    }
+
+    /// The number of args in the executor.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of args.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_arg("--verbose")
+    ///     .add_args(vec!["commit", "-S"]);
+    ///
+    /// assert_eq!(executor.arg_count(), 3);
+    /// ```
+    pub fn arg_count(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Sets the maximum allowed time for compilation in milliseconds.
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to set, or `-1` for no timeout. See
+    ///   [`Executor::disable_compile_timeout`].
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_compile_timeout(5000);
+    ///
+    /// assert_eq!(executor.compile_timeout, 5000);
+    /// ```
+    #[must_use]
+    pub fn set_compile_timeout(mut self, timeout: isize) -> Self {
+        self.compile_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum allowed time for execution in milliseconds.
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to set, or `-1` for no timeout. See
+    ///   [`Executor::disable_run_timeout`].
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_run_timeout(1500);
+    ///
+    /// assert_eq!(executor.run_timeout, 1500);
+    /// ```
+    #[must_use]
+    pub fn set_run_timeout(mut self, timeout: isize) -> Self {
+        self.run_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum allowed time for compilation and execution in
+    /// milliseconds at once.
+    ///
+    /// # Arguments
+    /// - `compile_ms` - The compilation timeout to set.
+    /// - `run_ms` - The execution timeout to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_timeouts(5000, 1500);
+    ///
+    /// assert_eq!(executor.compile_timeout, 5000);
+    /// assert_eq!(executor.run_timeout, 1500);
+    /// ```
+    #[must_use]
+    pub fn set_timeouts(self, compile_ms: isize, run_ms: isize) -> Self {
+        self.set_compile_timeout(compile_ms).set_run_timeout(run_ms)
+    }
+
+    /// Disables the compilation timeout, by setting it to `-1`. Only
+    /// instances configured to allow unlimited timeouts accept this,
+    /// the public Piston instance does not.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .disable_compile_timeout();
+    ///
+    /// assert_eq!(executor.compile_timeout, -1);
+    /// ```
+    #[must_use]
+    pub fn disable_compile_timeout(self) -> Self {
+        self.set_compile_timeout(-1)
+    }
+
+    /// Disables the execution timeout, by setting it to `-1`. Only
+    /// instances configured to allow unlimited timeouts accept this,
+    /// the public Piston instance does not.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .disable_run_timeout();
+    ///
+    /// assert_eq!(executor.run_timeout, -1);
+    /// ```
+    #[must_use]
+    pub fn disable_run_timeout(self) -> Self {
+        self.set_run_timeout(-1)
+    }
+
+    /// Sets the maximum allowed memory usage for compilation in bytes.
+    ///
+    /// # Arguments
+    /// - `limit` - The memory limit to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_compile_memory_limit(100_000_000);
+    ///
+    /// assert_eq!(executor.compile_memory_limit, 100_000_000);
+    /// ```
+    #[must_use]
+    pub fn set_compile_memory_limit(mut self, limit: isize) -> Self {
+        self.compile_memory_limit = limit;
+        self
+    }
+
+    /// Sets the maximum allowed memory usage for execution in bytes.
+    ///
+    /// # Arguments
+    /// - `limit` - The memory limit to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_run_memory_limit(100_000_000);
+    ///
+    /// assert_eq!(executor.run_memory_limit, 100_000_000);
+    /// ```
+    #[must_use]
+    pub fn set_run_memory_limit(mut self, limit: isize) -> Self {
+        self.run_memory_limit = limit;
+        self
+    }
+
+    /// Sets the maximum allowed memory usage for compilation and
+    /// execution in bytes at once.
+    ///
+    /// # Arguments
+    /// - `compile` - The compilation memory limit to set.
+    /// - `run` - The execution memory limit to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_memory_limits(200_000_000, 100_000_000);
+    ///
+    /// assert_eq!(executor.compile_memory_limit, 200_000_000);
+    /// assert_eq!(executor.run_memory_limit, 100_000_000);
+    /// ```
+    #[must_use]
+    pub fn set_memory_limits(self, compile: isize, run: isize) -> Self {
+        self.set_compile_memory_limit(compile)
+            .set_run_memory_limit(run)
+    }
+
+    /// Sets whether or not to request `stdout` as base64 encoded
+    /// binary output, if the Piston instance supports it. The output
+    /// can then be decoded with [`ExecResult::decoded_stdout`].
+    ///
+    /// # Arguments
+    /// - `binary` - Whether to request binary output.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_binary_output(true);
+    ///
+    /// assert!(executor.binary_output);
+    /// ```
+    #[must_use]
+    pub fn set_binary_output(mut self, binary: bool) -> Self {
+        self.binary_output = binary;
+        self
+    }
+
+    /// Sets whether or not to only compile the code. This is a best
+    /// effort approximation, as Piston always runs code after compiling
+    /// it. Instead, the smallest possible run timeout is sent,
+    /// regardless of [`Executor::run_timeout`]. Read the result with
+    /// [`ExecResponse::compiled_successfully`].
+    ///
+    /// # Arguments
+    /// - `compile_only` - Whether to only compile the code.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_compile_only(true);
+    ///
+    /// assert!(executor.compile_only);
+    /// ```
+    #[must_use]
+    pub fn set_compile_only(mut self, compile_only: bool) -> Self {
+        self.compile_only = compile_only;
+        self
+    }
+
+    /// Sets the interpreter to run the main file with. Piston has no
+    /// entrypoint option, so when executing, `#!` followed by the
+    /// entrypoint is prepended to the main [`File`] as a shebang line,
+    /// replacing any shebang it already has. The executor's files are
+    /// left unchanged, and main files that are not `utf8` encoded are
+    /// sent as is.
+    ///
+    /// # Arguments
+    /// - `entrypoint` - The interpreter, with or without a leading
+    ///   `#!`.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("bash")
+    ///     .add_file(piston_rs::File::default().set_content("echo $0"))
+    ///     .set_entrypoint("/usr/bin/env bash");
+    ///
+    /// assert_eq!(executor.entrypoint, Some("/usr/bin/env bash".to_string()));
+    /// assert!(executor.to_request_json().unwrap().contains("#!/usr/bin/env bash\\necho $0"));
+    /// ```
+    #[must_use]
+    pub fn set_entrypoint(mut self, entrypoint: &str) -> Self {
+        self.entrypoint = Some(entrypoint.trim_start_matches("#!").to_string());
+        self
+    }
+
+    /// Sets an extra field to send in the request body, such as an
+    /// option a newer Piston version supports that this crate does not
+    /// yet. Fields the executor already sends, like `language`, take
+    /// precedence, and are not overwritten.
+    ///
+    /// # Arguments
+    /// - `key` - The name of the field.
+    /// - `value` - The value of the field.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .set_extra("priority", serde_json::json!(1));
+    ///
+    /// assert_eq!(executor.extra["priority"], serde_json::json!(1));
+    /// assert!(executor.to_request_json().unwrap().contains("\"priority\": 1"));
+    /// ```
+    #[must_use]
+    pub fn set_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+
+    /// The pretty printed JSON body that is sent to Piston when
+    /// executing. Useful for debugging rejected requests.
+    ///
+    /// # Returns
+    /// - [`Result<String, serde_json::Error>`] - The JSON body, or the
+    ///   error if serialization failed.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"));
+    ///
+    /// let json = executor.to_request_json().unwrap();
+    ///
+    /// assert!(json.contains("\"language\": \"rust\""));
+    /// assert!(json.contains("\"name\": \"main.rs\""));
+    /// ```
+    pub fn to_request_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.payload())
+    }
+
+    /// Checks the executor against the constraints Piston places on
+    /// an execute request. Every problem is reported, rather than only
+    /// the first.
+    ///
+    /// # Returns
+    /// - [`Result<(), Vec<String>>`] - Nothing if the executor is
+    ///   valid, otherwise a description of each problem.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_content("fn main() {}"));
+    ///
+    /// assert!(executor.validate_schema().is_ok());
+    ///
+    /// let problems = piston_rs::Executor::new()
+    ///     .set_run_timeout(-5)
+    ///     .validate_schema()
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(problems.len(), 2);
+    /// ```
+    pub fn validate_schema(&self) -> Result<(), Vec<String>> {
+        let mut problems = vec![];
+
+        if self.language.trim().is_empty() {
+            problems.push("language must not be empty".to_string());
+        }
+
+        let limits = [
+            ("compile_timeout", self.compile_timeout),
+            ("run_timeout", self.run_timeout),
+            ("compile_memory_limit", self.compile_memory_limit),
+            ("run_memory_limit", self.run_memory_limit),
+        ];
+
+        for (name, value) in limits {
+            if value < 0 && value != -1 {
+                problems.push(format!(
+                    "{} must be non negative or -1, got {}",
+                    name, value
+                ));
+            }
+        }
+
+        for (i, file) in self.files.iter().enumerate() {
+            if !ENCODINGS.contains(&file.encoding.as_str()) {
+                problems.push(format!(
+                    "files[{}] has invalid encoding '{}', must be one of: {}",
+                    i,
+                    file.encoding,
+                    ENCODINGS.join(", ")
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Explains why the executor would be rejected by Piston, or is
+    /// unlikely to do what was intended, in messages suitable for
+    /// showing to users. Unlike [`Executor::validate_schema`], files
+    /// are referred to by name.
+    ///
+    /// # Returns
+    /// - [`Vec<String>`] - A message for each problem, which is empty
+    ///   if none were found.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default().set_name("main.rs").set_content("fn main() {}"));
+    ///
+    /// assert!(executor.diagnostics().is_empty());
+    ///
+    /// let diagnostics = piston_rs::Executor::new().diagnostics();
+    /// assert_eq!(diagnostics, vec!["language is empty", "no files provided"]);
+    /// ```
+    pub fn diagnostics(&self) -> Vec<String> {
+        let mut diagnostics = vec![];
+
+        if self.language.trim().is_empty() {
+            diagnostics.push("language is empty".to_string());
+        }
+
+        if self.version.trim().is_empty() {
+            diagnostics.push("version is empty, use '*' for the latest".to_string());
+        }
+
+        if self.files.is_empty() {
+            diagnostics.push("no files provided".to_string());
+        }
+
+        for (i, file) in self.files.iter().enumerate() {
+            let name = match file.name.is_empty() {
+                true => format!("#{}", i + 1),
+                false => format!("'{}'", file.name),
+            };
+
+            if !ENCODINGS.contains(&file.encoding.as_str()) {
+                diagnostics.push(format!(
+                    "file {} has invalid encoding '{}'",
+                    name, file.encoding
+                ));
+            }
+
+            if file.content.trim().is_empty() {
+                diagnostics.push(format!("file {} is empty", name));
+            }
+
+            let duplicate = self.files[..i].iter().any(|f| f.name == file.name);
+            if !file.name.is_empty() && duplicate {
+                diagnostics.push(format!("file {} is provided more than once", name));
+            }
+        }
+
+        let limits = [
+            ("compile timeout", self.compile_timeout),
+            ("run timeout", self.run_timeout),
+            ("compile memory limit", self.compile_memory_limit),
+            ("run memory limit", self.run_memory_limit),
+        ];
+
+        for (name, value) in limits {
+            if value < 0 && value != -1 {
+                diagnostics.push(format!("{} is {}, but must be -1 or more", name, value));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// A stable hash of everything sent to Piston, such as for caching
+    /// results of identical submissions. Executors that would send the
+    /// same request hash equally, across processes and platforms.
+    ///
+    /// # Returns
+    /// - [`u64`] - The hash.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// assert_eq!(executor.content_hash(), executor.clone().content_hash());
+    /// assert_ne!(executor.content_hash(), executor.set_stdin("42").content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let payload = self.payload();
+        let mut hasher = Fnv1a::default();
+
+        payload.language.hash(&mut hasher);
+        payload.version.hash(&mut hasher);
+        hasher.write_usize(payload.files.len());
+        for file in &payload.files {
+            file.name.hash(&mut hasher);
+            file.content.hash(&mut hasher);
+            file.encoding.hash(&mut hasher);
+        }
+
+        payload.stdin.hash(&mut hasher);
+        payload.args.hash(&mut hasher);
+        payload.compile_timeout.hash(&mut hasher);
+        payload.run_timeout.hash(&mut hasher);
+        payload.compile_memory_limit.hash(&mut hasher);
+        payload.run_memory_limit.hash(&mut hasher);
+        payload.binary_output.hash(&mut hasher);
+
+        let mut extra = payload.extra.iter().collect::<Vec<_>>();
+        extra.sort_unstable_by_key(|(key, _)| *key);
+        hasher.write_usize(extra.len());
+        for (key, value) in extra {
+            key.hash(&mut hasher);
+            value.to_string().hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    /// The executor as it should be sent to Piston, with any client
+    /// side options applied.
+    ///
+    /// # Returns
+    /// - [`Cow<Executor>`] - The executor to send.
+    pub(crate) fn payload(&self) -> Cow<'_, Executor> {
+        let shadowed = self
+            .extra
+            .keys()
+            .any(|k| PAYLOAD_FIELDS.contains(&k.as_str()));
+        if !self.compile_only && self.entrypoint.is_none() && !shadowed {
+            return Cow::Borrowed(self);
+        }
+
+        let mut payload = self.clone();
+        if self.compile_only {
+            payload.run_timeout = COMPILE_ONLY_RUN_TIMEOUT;
+        }
+
+        if shadowed {
+            payload
+                .extra
+                .retain(|k, _| !PAYLOAD_FIELDS.contains(&k.as_str()));
+        }
+
+        if let Some(entrypoint) = &self.entrypoint {
+            if let Some(main) = payload.files.first_mut().filter(|f| f.encoding == "utf8") {
+                let content = match main.content.strip_prefix("#!") {
+                    Some(shebang) => shebang.split_once('\n').map_or("", |(_, rest)| rest),
+                    None => &main.content,
+                };
+
+                main.content = format!("#!{}\n{}", entrypoint, content);
+            }
+        }
+
+        Cow::Owned(payload)
+    }
+}
+
+/// A builder for an [`Executor`], which validates that the required
+/// fields were provided when [`ExecutorBuilder::build`] is called.
+///
+/// Unlike the builder flow provided by the `Executor` itself, an
+/// executor with no language or files can not be built.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutorBuilder {
+    /// The executor being built.
+    executor: Executor,
+}
+
+impl ExecutorBuilder {
+    /// Creates a new executor builder.
+    ///
+    /// # Returns
+    /// - [`ExecutorBuilder`] - The new blank ExecutorBuilder.
+    ///
+    /// # Example
+    /// ```
+    /// let builder = piston_rs::ExecutorBuilder::new();
+    ///
+    /// assert!(builder.build().is_err());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            executor: Executor::new(),
+        }
+    }
+
+    /// Builds the [`Executor`], validating its required fields.
+    ///
+    /// # Returns
+    /// - [`Result<Executor, String>`] - The new Executor, or the
+    ///   reason it is invalid.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::ExecutorBuilder::new()
+    ///     .set_language("rust")
+    ///     .add_file(piston_rs::File::default())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(executor.language, "rust".to_string());
+    /// assert_eq!(executor.files.len(), 1);
+    ///
+    /// let result = piston_rs::ExecutorBuilder::new()
+    ///     .add_file(piston_rs::File::default())
+    ///     .build();
+    ///
+    /// assert_eq!(result.unwrap_err(), "Executor language must be set".to_string());
+    /// ```
+    pub fn build(self) -> Result<Executor, String> {
+        if self.executor.language.is_empty() {
+            return Err("Executor language must be set".to_string());
+        }
+
+        if self.executor.files.is_empty() {
+            return Err("Executor must have at least one file".to_string());
+        }
+
+        Ok(self.executor)
+    }
+
+    /// Sets the language to use for execution. See
+    /// [`Executor::set_language`].
+    ///
+    /// # Arguments
+    /// - `language` - The language to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_language(mut self, language: &str) -> Self {
+        self.executor = self.executor.set_language(language);
+        self
+    }
+
+    /// Sets the version of the language to use for execution. See
+    /// [`Executor::set_version`].
+    ///
+    /// # Arguments
+    /// - `version` - The version to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_version(mut self, version: &str) -> Self {
+        self.executor = self.executor.set_version(version);
+        self
+    }
+
+    /// Adds a [`File`] containing the code to be executed. See
+    /// [`Executor::add_file`].
+    ///
+    /// # Arguments
+    /// - `file` - The file to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn add_file(mut self, file: File) -> Self {
+        self.executor = self.executor.add_file(file);
+        self
+    }
+
+    /// Adds multiple [`File`]'s containing the code to be executed.
+    /// See [`Executor::add_files`].
+    ///
+    /// # Arguments
+    /// - `files` - The files to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn add_files(mut self, files: Vec<File>) -> Self {
+        self.executor = self.executor.add_files(files);
+        self
+    }
+
+    /// Sets the text to pass as `stdin` to the program. See
+    /// [`Executor::set_stdin`].
+    ///
+    /// # Arguments
+    /// - `stdin` - The text to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_stdin(mut self, stdin: &str) -> Self {
+        self.executor = self.executor.set_stdin(stdin);
+        self
+    }
+
+    /// Adds an arg to be passed as a command line argument. See
+    /// [`Executor::add_arg`].
+    ///
+    /// # Arguments
+    /// - `arg` - The arg to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn add_arg(mut self, arg: &str) -> Self {
+        self.executor = self.executor.add_arg(arg);
+        self
+    }
+
+    /// Adds multiple args to be passed as command line arguments. See
+    /// [`Executor::add_args`].
+    ///
+    /// # Arguments
+    /// - `args` - The args to add.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn add_args(mut self, args: Vec<&str>) -> Self {
+        self.executor = self.executor.add_args(args);
+        self
+    }
+
+    /// Sets the maximum allowed time for compilation in milliseconds.
+    /// See [`Executor::set_compile_timeout`].
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_compile_timeout(mut self, timeout: isize) -> Self {
+        self.executor = self.executor.set_compile_timeout(timeout);
+        self
+    }
+
+    /// Sets the maximum allowed time for execution in milliseconds.
+    /// See [`Executor::set_run_timeout`].
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_run_timeout(mut self, timeout: isize) -> Self {
+        self.executor = self.executor.set_run_timeout(timeout);
+        self
+    }
+
+    /// Sets the maximum allowed memory usage for compilation in bytes.
+    /// See [`Executor::set_compile_memory_limit`].
+    ///
+    /// # Arguments
+    /// - `limit` - The memory limit to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_compile_memory_limit(mut self, limit: isize) -> Self {
+        self.executor = self.executor.set_compile_memory_limit(limit);
+        self
+    }
+
+    /// Sets the maximum allowed memory usage for execution in bytes.
+    /// See [`Executor::set_run_memory_limit`].
+    ///
+    /// # Arguments
+    /// - `limit` - The memory limit to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_run_memory_limit(mut self, limit: isize) -> Self {
+        self.executor = self.executor.set_run_memory_limit(limit);
+        self
+    }
+
+    /// Sets whether or not to request `stdout` as base64 encoded
+    /// binary output. See [`Executor::set_binary_output`].
+    ///
+    /// # Arguments
+    /// - `binary` - Whether to request binary output.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_binary_output(mut self, binary: bool) -> Self {
+        self.executor = self.executor.set_binary_output(binary);
+        self
+    }
+
+    /// Sets whether or not to only compile the code. See
+    /// [`Executor::set_compile_only`].
+    ///
+    /// # Arguments
+    /// - `compile_only` - Whether to only compile the code.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_compile_only(mut self, compile_only: bool) -> Self {
+        self.executor = self.executor.set_compile_only(compile_only);
+        self
+    }
+
+    /// Sets the interpreter to run the main file with. See
+    /// [`Executor::set_entrypoint`].
+    ///
+    /// # Arguments
+    /// - `entrypoint` - The interpreter, with or without a leading
+    ///   `#!`.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_entrypoint(mut self, entrypoint: &str) -> Self {
+        self.executor = self.executor.set_entrypoint(entrypoint);
+        self
+    }
+
+    /// Sets an extra field to send in the request body. See
+    /// [`Executor::set_extra`].
+    ///
+    /// # Arguments
+    /// - `key` - The name of the field.
+    /// - `value` - The value of the field.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.executor = self.executor.set_extra(key, value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test_execution_result {
+    use super::ExecResponse;
+    use super::ExecResult;
+    use super::RawExecResponse;
+    use super::Stage;
+
+    /// Generates an ExecResult for testing
+    fn generate_result(stdout: &str, stderr: &str, code: isize) -> ExecResult {
+        ExecResult {
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            output: format!("{}\n{}", stdout, stderr),
+            code: Some(code),
+            signal: None,
+            binary: false,
+            files: None,
+            status: None,
+        }
+    }
+
+    /// Generates an ExecResponse for testing.
+    fn generate_response(status: u16) -> ExecResponse {
+        ExecResponse {
+            language: "rust".to_string(),
+            version: "1.50.0".to_string(),
+            run: generate_result("Be unique.", "", 0),
+            runs: None,
+            compile: None,
+            status,
+            compile_timeout: None,
+            run_timeout: None,
+            compile_memory_limit: None,
+            run_memory_limit: None,
+        }
+    }
+
+    #[test]
     fn test_response_is_ok() {
         let response = generate_response(200);
 
-        assert!(response.is_ok());
-        assert!(!response.is_err());
+        assert!(response.is_ok());
+        assert!(!response.is_err());
+    }
+
+    #[test]
+    fn test_response_into_result_ok() {
+        let response = generate_response(200);
+
+        assert!(response.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_response_into_result_failed() {
+        let mut response = generate_response(200);
+        response.run = generate_result("", "Error!", 1);
+        assert_eq!(response.into_result().unwrap_err().exit_code(), Some(1));
+
+        let mut response = generate_response(200);
+        response.compile = Some(generate_result("", "error[E0425]", 1));
+        assert!(response.into_result().is_err());
+
+        assert!(generate_response(500).into_result().is_err());
+    }
+
+    #[test]
+    fn test_response_is_err() {
+        let response = generate_response(400);
+
+        assert!(!response.is_ok());
+        assert!(response.is_err());
+    }
+
+    #[test]
+    fn test_response_golden_json() {
+        // Piston does not send the status, the client adds it.
+        let mut body: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/exec_response.json")).unwrap();
+        body["status"] = serde_json::json!(200);
+
+        let response: ExecResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.language, "rust".to_string());
+        assert_eq!(response.version, "1.50.0".to_string());
+        assert_eq!(response.run.stdout, "42\n".to_string());
+        assert_eq!(response.run.output, "42\n".to_string());
+        assert_eq!(response.run.code, Some(0));
+        assert_eq!(response.run.signal, None);
+        assert_eq!(response.run.status, None);
+
+        let files = response.run.files.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "out.txt".to_string());
+        assert_eq!(files[0].content, "done".to_string());
+        assert_eq!(files[0].encoding, "utf8".to_string());
+
+        let compile = response.compile.unwrap();
+        assert_eq!(compile.stderr, "warning: unused variable\n".to_string());
+        assert_eq!(compile.code, Some(0));
+
+        assert_eq!(response.compile_timeout, Some(10000));
+        assert_eq!(response.run_timeout, Some(3000));
+        assert_eq!(response.compile_memory_limit, Some(-1));
+        assert_eq!(response.run_memory_limit, Some(100_000_000));
+    }
+
+    #[test]
+    fn test_response_single_run() {
+        let body = r#"{
+            "language": "python",
+            "version": "3.10.0",
+            "run": {"stdout": "1\n", "stderr": "", "output": "1\n", "code": 0, "signal": null}
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert!(response.runs.is_none());
+        assert_eq!(response.run.stdout, "1\n".to_string());
+    }
+
+    #[test]
+    fn test_response_all_runs() {
+        let mut response = generate_response(200);
+        assert_eq!(response.all_runs().len(), 1);
+        assert_eq!(response.all_runs()[0].stdout, "Be unique.".to_string());
+
+        response.runs = Some(vec![
+            generate_result("1", "", 0),
+            generate_result("2", "", 0),
+        ]);
+        let stdout: Vec<&str> = response
+            .all_runs()
+            .iter()
+            .map(|r| r.stdout.as_str())
+            .collect();
+        assert_eq!(stdout, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_response_multiple_runs() {
+        let body = r#"{
+            "language": "python",
+            "version": "3.10.0",
+            "run": {"stdout": "2\n", "stderr": "", "output": "2\n", "code": 0, "signal": null},
+            "runs": [
+                {"stdout": "1\n", "stderr": "", "output": "1\n", "code": 0, "signal": null},
+                {"stdout": "2\n", "stderr": "", "output": "2\n", "code": 0, "signal": null}
+            ]
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        let runs = response.runs.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].stdout, "1\n".to_string());
+        assert_eq!(response.run.stdout, "2\n".to_string());
+    }
+
+    #[test]
+    fn test_response_with_applied_limits() {
+        let body = r#"{
+            "language": "rust",
+            "version": "1.50.0",
+            "run": {"stdout": "", "stderr": "", "output": "", "code": 0, "signal": null},
+            "compile_timeout": 10000,
+            "run_timeout": 3000,
+            "compile_memory_limit": -1,
+            "run_memory_limit": 100000000
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.compile_timeout, Some(10000));
+        assert_eq!(response.run_timeout, Some(3000));
+        assert_eq!(response.compile_memory_limit, Some(-1));
+        assert_eq!(response.run_memory_limit, Some(100_000_000));
+    }
+
+    #[test]
+    fn test_response_empty_compile_stage() {
+        let body = r#"{
+            "language": "python",
+            "version": "3.10.0",
+            "run": {"stdout": "42\n", "stderr": "", "output": "42\n", "code": 0, "signal": null},
+            "compile": {}
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert!(response.compile.is_none());
+        assert_eq!(response.run.stdout, "42\n".to_string());
+    }
+
+    #[test]
+    fn test_response_null_and_present_compile_stage() {
+        let body = r#"{
+            "language": "rust",
+            "version": "1.50.0",
+            "run": {"stdout": "", "stderr": "", "output": "", "code": 0, "signal": null},
+            "compile": null
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert!(response.compile.is_none());
+
+        let body = r#"{
+            "language": "rust",
+            "version": "1.50.0",
+            "run": {"stdout": "", "stderr": "", "output": "", "code": 0, "signal": null},
+            "compile": {"code": 0}
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert!(response.compile.unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_response_without_applied_limits() {
+        let body = r#"{
+            "language": "rust",
+            "version": "1.50.0",
+            "run": {"stdout": "", "stderr": "", "output": "", "code": 0, "signal": null}
+        }"#;
+
+        let response: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert!(response.compile_timeout.is_none());
+        assert!(response.run_timeout.is_none());
+        assert!(response.compile_memory_limit.is_none());
+        assert!(response.run_memory_limit.is_none());
+    }
+
+    #[test]
+    fn test_response_output_files() {
+        let body = r#"{
+            "language": "python",
+            "version": "3.10.0",
+            "run": {
+                "stdout": "", "stderr": "", "output": "", "code": 0, "signal": null,
+                "files": [
+                    {"name": "out.txt", "content": "hello", "encoding": "utf8"},
+                    {"name": "out.bin", "content": "AAEC", "encoding": "base64"}
+                ]
+            }
+        }"#;
+
+        let raw: RawExecResponse = serde_json::from_str(body).unwrap();
+        let mut response = generate_response(200);
+        response.run = raw.run;
+
+        let files = response.output_files();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name, "out.txt".to_string());
+        assert_eq!(files[0].content, "hello".to_string());
+        assert_eq!(files[1].encoding, "base64".to_string());
+    }
+
+    #[test]
+    fn test_response_without_output_files() {
+        let response = generate_response(200);
+
+        assert!(response.run.files.is_none());
+        assert!(response.output_files().is_empty());
+    }
+
+    #[test]
+    fn test_response_unknown_fields() {
+        let body = r#"{
+            "language": "rust",
+            "version": "1.50.0",
+            "run": {
+                "stdout": "42", "stderr": "", "output": "42", "code": 0, "signal": null,
+                "cpu_time": 12, "wall_time": 30, "memory": 1024
+            },
+            "compile": {"stdout": "", "stderr": "", "output": "", "code": 0, "extra": {}},
+            "status": 200,
+            "region": "eu-west"
+        }"#;
+
+        let raw: RawExecResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(raw.run.stdout, "42".to_string());
+        assert!(raw.compile.unwrap().is_ok());
+
+        let response: ExecResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.run.code, Some(0));
+        assert_eq!(response.status, 200);
+
+        let result: ExecResult =
+            serde_json::from_str(r#"{"stdout": "1", "new_field": [1, 2]}"#).unwrap();
+        assert_eq!(result.stdout, "1".to_string());
+    }
+
+    #[test]
+    fn test_response_is_timeout() {
+        let mut response = generate_response(200);
+        response.run.code = None;
+        response.run.signal = Some("SIGKILL".to_string());
+        assert!(response.is_timeout());
+
+        let mut response = generate_response(200);
+        let mut compile = generate_result("", "", 1);
+        compile.status = Some("TO".to_string());
+        response.compile = Some(compile);
+        assert!(response.is_timeout());
+    }
+
+    #[test]
+    fn test_response_compile_timed_out() {
+        let mut response = generate_response(200);
+        response.compile = Some(ExecResult {
+            signal: Some("SIGKILL".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(response.which_stage_timed_out(), Some(Stage::Compile));
+    }
+
+    #[test]
+    fn test_response_run_timed_out() {
+        let mut response = generate_response(200);
+        response.compile = Some(generate_result("", "", 0));
+        response.run.status = Some("TO".to_string());
+
+        assert_eq!(response.which_stage_timed_out(), Some(Stage::Run));
+        assert_eq!(generate_response(200).which_stage_timed_out(), None);
+    }
+
+    #[test]
+    fn test_response_not_timeout() {
+        let response = generate_response(200);
+        assert!(!response.is_timeout());
+
+        let mut response = generate_response(200);
+        response.run.signal = Some("SIGKILL".to_string());
+        response.run.status = Some("SG".to_string());
+        assert!(!response.is_timeout());
+    }
+
+    #[test]
+    fn test_response_stages() {
+        let mut response = generate_response(200);
+        assert_eq!(response.run_stage().stdout, "Be unique.".to_string());
+        assert!(response.compile_stage().is_none());
+
+        response.compile = Some(generate_result("", "warning: unused", 0));
+        assert_eq!(
+            response.compile_stage().unwrap().stderr,
+            "warning: unused".to_string()
+        );
+        assert_eq!(response.run_stage(), &response.run);
+    }
+
+    #[test]
+    fn test_response_exit_code() {
+        let mut response = generate_response(200);
+        assert_eq!(response.exit_code(), Some(0));
+
+        response.run.code = None;
+        response.run.signal = Some("SIGKILL".to_string());
+        assert_eq!(response.exit_code(), None);
+    }
+
+    #[test]
+    fn test_response_compiled_successfully() {
+        let mut response = generate_response(200);
+        assert!(!response.compiled_successfully());
+
+        response.compile = Some(generate_result("", "", 0));
+        assert!(response.compiled_successfully());
+
+        response.compile = Some(generate_result("", "error[E0308]", 1));
+        assert!(!response.compiled_successfully());
+    }
+
+    #[test]
+    fn test_result_is_ok() {
+        let result = generate_result("Hello, world", "", 0);
+
+        assert!(result.is_ok());
+        assert!(!result.is_err());
+    }
+
+    #[test]
+    fn test_result_is_err() {
+        let result = generate_result("", "Error!", 1);
+
+        assert!(!result.is_ok());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_result_exit_success() {
+        assert!(generate_result("", "", 0).exit_success());
+        assert!(!generate_result("", "", 1).exit_success());
+
+        let mut killed = generate_result("", "", 0);
+        killed.code = None;
+        killed.signal = Some("SIGKILL".to_string());
+
+        assert!(!killed.is_ok());
+        assert!(!killed.exit_success());
+    }
+
+    #[test]
+    fn test_is_err_with_stdout() {
+        let result = generate_result("Hello, world", "Error!", 1);
+
+        assert!(!result.is_ok());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_produced_output() {
+        assert!(generate_result("Hello, world", "", 0).produced_output());
+        assert!(generate_result("", "Error!", 1).produced_output());
+    }
+
+    #[test]
+    fn test_no_output_produced() {
+        assert!(!generate_result("", "", 0).produced_output());
+        assert!(!generate_result(" \n", "\t\r\n", 0).produced_output());
+    }
+
+    #[test]
+    fn test_stdout_matches_exact() {
+        let result = generate_result("1\n2\n", "", 0);
+
+        assert!(result.stdout_matches("1\n2\n", false));
+        assert!(result.stdout_matches("1\n2\n", true));
+    }
+
+    #[test]
+    fn test_stdout_matches_trailing_whitespace() {
+        let result = generate_result("1  \r\n2\n\n", "", 0);
+
+        assert!(result.stdout_matches("1\n2", true));
+        assert!(!result.stdout_matches("1\n2", false));
+    }
+
+    #[test]
+    fn test_stdout_mismatch() {
+        let result = generate_result("1\n2\n", "", 0);
+
+        assert!(!result.stdout_matches("1\n3\n", true));
+        assert!(!result.stdout_matches("  1\n2\n", true));
+        assert!(!result.stdout_matches("1\n\n2\n", true));
+    }
+
+    #[test]
+    fn test_output_lines() {
+        let result = generate_result("test a ... ok\ntest b ... ok\r\n\ndone\n", "warn\n", 0);
+
+        assert_eq!(result.stdout_lines().count(), 4);
+        assert_eq!(result.stdout_lines().nth(1), Some("test b ... ok"));
+        assert_eq!(result.stdout_lines().last(), Some("done"));
+        assert_eq!(result.stderr_lines().count(), 1);
     }
 
     #[test]
-    fn test_response_is_err() {
-        let response = generate_response(400);
+    fn test_effective_output() {
+        let result = generate_result("Hello", "Error!", 1);
 
-        assert!(!response.is_ok());
-        assert!(response.is_err());
+        assert_eq!(result.effective_output(), "Hello\nError!".to_string());
     }
 
     #[test]
-    fn test_result_is_ok() {
-        let result = generate_result("Hello, world", "", 0);
+    fn test_effective_output_missing() {
+        let body = r#"{"stdout": "42\n", "stderr": "warning\n", "code": 0, "signal": null}"#;
+        let result: ExecResult = serde_json::from_str(body).unwrap();
 
-        assert!(result.is_ok());
-        assert!(!result.is_err());
+        assert!(result.output.is_empty());
+        assert_eq!(result.effective_output(), "42\nwarning\n".to_string());
     }
 
     #[test]
-    fn test_result_is_err() {
-        let result = generate_result("", "Error!", 1);
+    fn test_output_bytes() {
+        let result = generate_result("naïve café", "⚠ warning", 0);
 
-        assert!(!result.is_ok());
-        assert!(result.is_err());
+        assert_eq!(result.stdout_bytes(), 12);
+        assert_eq!(result.stdout.chars().count(), 10);
+        assert_eq!(result.stderr_bytes(), 11);
+        assert_eq!(result.stderr.chars().count(), 9);
+        assert_eq!(result.output_bytes(), 24);
     }
 
     #[test]
-    fn test_is_err_with_stdout() {
-        let result = generate_result("Hello, world", "Error!", 1);
+    fn test_likely_truncated_heuristic() {
+        let result = generate_result(&"a".repeat(100), "", 0);
+        assert!(result.likely_truncated(100));
+        assert!(!result.likely_truncated(101));
 
-        assert!(!result.is_ok());
-        assert!(result.is_err());
+        let result = generate_result("", &"e".repeat(100), 1);
+        assert!(result.likely_truncated(100));
+        assert!(!result.likely_truncated(101));
+    }
+
+    #[test]
+    fn test_likely_truncated_status() {
+        let body = r#"{"stdout": "abc", "stderr": "", "code": null, "status": "OL"}"#;
+        let result: ExecResult = serde_json::from_str(body).unwrap();
+        assert!(result.likely_truncated(1024));
+
+        let mut result = generate_result(&"a".repeat(100), "", 0);
+        result.status = Some("TO".to_string());
+        assert!(!result.likely_truncated(100));
+    }
+
+    #[test]
+    fn test_decoded_stdout_binary() {
+        let mut result = generate_result("iVBORw0KGgo=\n", "", 0);
+        result.binary = true;
+
+        let decoded = result.decoded_stdout().unwrap();
+        assert_eq!(
+            decoded,
+            vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']
+        );
+    }
+
+    #[test]
+    fn test_decoded_stdout_invalid_base64() {
+        let mut result = generate_result("not base64!", "", 0);
+        result.binary = true;
+
+        assert!(result.decoded_stdout().is_err());
+    }
+
+    #[test]
+    fn test_decoded_stdout_not_binary() {
+        let result = generate_result("iVBORw0KGgo=", "", 0);
+
+        assert_eq!(result.decoded_stdout().unwrap(), b"iVBORw0KGgo=".to_vec());
+    }
+}
+
+#[cfg(test)]
+mod test_executor_builder {
+    use super::ExecutorBuilder;
+    use super::File;
+
+    #[test]
+    fn test_build_ok() {
+        let executor = ExecutorBuilder::new()
+            .set_language("Rust")
+            .set_version("1.50.0")
+            .add_file(File::default().set_name("main.rs"))
+            .add_args(vec!["--verbose"])
+            .set_stdin("42")
+            .set_run_timeout(1500)
+            .build()
+            .unwrap();
+
+        assert_eq!(executor.language, "rust".to_string());
+        assert_eq!(executor.version, "1.50.0".to_string());
+        assert_eq!(executor.files[0].name, "main.rs".to_string());
+        assert_eq!(executor.args, vec!["--verbose".to_string()]);
+        assert_eq!(executor.stdin, "42".to_string());
+        assert_eq!(executor.run_timeout, 1500);
+    }
+
+    #[test]
+    fn test_build_missing_language() {
+        let result = ExecutorBuilder::new().add_file(File::default()).build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Executor language must be set".to_string()
+        );
+    }
+
+    #[test]
+    fn test_build_missing_files() {
+        let result = ExecutorBuilder::new().set_language("rust").build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Executor must have at least one file".to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_executor_private {
+    use std::io::Cursor;
+
+    use super::Executor;
+    use super::File;
+    use super::PistonError;
+    use super::RuntimeSpec;
+    use super::COMPILE_ONLY_RUN_TIMEOUT;
+
+    #[test]
+    fn test_executor_golden_json() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .set_version("1.50.0")
+            .add_file(
+                File::default()
+                    .set_name("main.rs")
+                    .set_content("fn main() { println!(\"42\"); }"),
+            )
+            .add_file(
+                File::default()
+                    .set_name("data.bin")
+                    .set_content("AAEC")
+                    .set_encoding("base64"),
+            )
+            .set_stdin("input")
+            .add_arg("--verbose")
+            .set_timeouts(10000, 3000)
+            .set_memory_limits(-1, 100_000_000)
+            .set_binary_output(true);
+
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/executor.json")).unwrap();
+        let actual = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_payload_compile_only() {
+        let executor = Executor::new().set_run_timeout(5000);
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["run_timeout"], 5000);
+        assert!(json.get("compile_only").is_none());
+
+        let executor = executor.set_compile_only(true);
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["run_timeout"], COMPILE_ONLY_RUN_TIMEOUT);
+        assert_eq!(executor.run_timeout, 5000);
+    }
+
+    #[test]
+    fn test_payload_extra_fields() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .add_file(File::default().set_content("fn main() {}"))
+            .set_extra("priority", serde_json::json!(1))
+            .set_extra("language", serde_json::json!("python"));
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["priority"], 1);
+        assert_eq!(json["language"], "rust");
+        assert_eq!(json["version"], "*");
+        assert_eq!(json["files"][0]["content"], "fn main() {}");
+
+        let request = executor.to_request_json().unwrap();
+        assert_eq!(request.matches("\"language\"").count(), 1);
+    }
+
+    #[test]
+    fn test_extra_fields_content_hash() {
+        let executor = Executor::new().set_language("rust");
+        let extra = executor.clone().set_extra("priority", serde_json::json!(1));
+
+        assert_ne!(executor.content_hash(), extra.content_hash());
+        assert_eq!(extra.content_hash(), extra.clone().content_hash());
+    }
+
+    #[test]
+    fn test_payload_entrypoint_unset() {
+        let executor = Executor::new().add_file(File::default().set_content("echo 42"));
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["files"][0]["content"], "echo 42");
+        assert!(json.get("entrypoint").is_none());
+    }
+
+    #[test]
+    fn test_payload_entrypoint() {
+        let executor = Executor::new()
+            .add_file(File::default().set_content("#!/bin/sh\necho 42"))
+            .add_file(File::default().set_content("echo lib"))
+            .set_entrypoint("#!/usr/bin/env bash");
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["files"][0]["content"], "#!/usr/bin/env bash\necho 42");
+        assert_eq!(json["files"][1]["content"], "echo lib");
+        assert!(json.get("entrypoint").is_none());
+        assert_eq!(executor.files[0].content, "#!/bin/sh\necho 42".to_string());
+    }
+
+    #[test]
+    fn test_payload_entrypoint_non_utf8() {
+        let executor = Executor::new()
+            .add_file(File::base64("main.sh", "echo 42"))
+            .set_entrypoint("/bin/bash");
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+
+        assert_eq!(json["files"][0]["content"], executor.files[0].content);
+    }
+
+    #[test]
+    fn test_set_encoding_for_all_files() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.sh"))
+            .add_file(File::default().set_name("b.sh"))
+            .add_file(File::default().set_name("c.sh"));
+
+        assert!(executor.files.iter().all(|f| f.encoding == "utf8"));
+
+        executor.set_encoding("base64").unwrap();
+        assert!(executor.files.iter().all(|f| f.encoding == "base64"));
+    }
+
+    #[test]
+    fn test_set_encoding_invalid() {
+        let mut executor = Executor::new().add_file(File::default());
+
+        match executor.set_encoding("utf16") {
+            Err(PistonError::InvalidExecutor(details)) => {
+                assert_eq!(
+                    details,
+                    "Invalid encoding 'utf16', must be one of: utf8, hex, base64".to_string()
+                );
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        assert_eq!(executor.files[0].encoding, "utf8".to_string());
+    }
+
+    #[test]
+    fn test_to_request_json() {
+        let executor = Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("print(42)"))
+            .set_compile_only(true);
+
+        let json = executor.to_request_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["language"], "python");
+        assert_eq!(value["files"][0]["content"], "print(42)");
+        assert_eq!(value["run_timeout"], COMPILE_ONLY_RUN_TIMEOUT);
+
+        for key in [
+            "compile_timeout",
+            "compile_memory_limit",
+            "run_memory_limit",
+        ] {
+            assert!(value.get(key).is_some(), "missing key {}", key);
+        }
+    }
+
+    #[test]
+    fn test_with_language_version() {
+        let executor = Executor::new()
+            .set_language("python")
+            .set_version("3.10.0")
+            .add_file(File::default().set_content("print(input())"))
+            .set_stdin("42")
+            .add_args(vec!["-v", "--fast"])
+            .set_run_timeout(5000)
+            .set_binary_output(true);
+
+        let other = executor.with_language_version("ruby", "3.0.1");
+
+        assert_eq!(other.language, "ruby".to_string());
+        assert_eq!(other.version, "3.0.1".to_string());
+        assert_eq!(other.files, executor.files);
+        assert_eq!(other.stdin, executor.stdin);
+        assert_eq!(other.args, executor.args);
+        assert_eq!(other.run_timeout, executor.run_timeout);
+        assert_eq!(other.binary_output, executor.binary_output);
+        assert_eq!(executor.language, "python".to_string());
+        assert_eq!(executor.version, "3.10.0".to_string());
+    }
+
+    #[test]
+    fn test_args_owned() {
+        let input: Vec<String> = "build --release".split(' ').map(String::from).collect();
+        let mut executor = Executor::new().add_arg("cargo").add_args_owned(input);
+
+        assert_eq!(executor.args, vec!["cargo", "build", "--release"]);
+
+        executor.set_args_owned(vec!["test".to_string()]);
+        assert_eq!(executor.args, vec!["test".to_string()]);
+    }
+
+    #[test]
+    fn test_append_to_main_empty() {
+        let mut executor = Executor::new();
+        executor.append_to_main("print(42)");
+
+        assert_eq!(executor.files.len(), 1);
+        assert_eq!(executor.files[0].content, "print(42)".to_string());
+        assert_eq!(executor.files[0].encoding, "utf8".to_string());
+    }
+
+    #[test]
+    fn test_append_to_main_existing() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("main.py").set_content("x = 41\n"))
+            .add_file(File::default().set_name("util.py"));
+
+        executor.append_to_main("print(x + 1)\n");
+
+        assert_eq!(executor.files.len(), 2);
+        assert_eq!(
+            executor.files[0].content,
+            "x = 41\nprint(x + 1)\n".to_string()
+        );
+        assert!(executor.files[1].content.is_empty());
+    }
+
+    #[test]
+    fn test_validate_schema_ok() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .add_file(File::default().set_content("fn main() {}"))
+            .set_compile_memory_limit(-1)
+            .set_run_timeout(0);
+
+        assert!(executor.validate_schema().is_ok());
+    }
+
+    #[test]
+    fn test_validate_schema_many_problems() {
+        let mut executor = Executor::new()
+            .add_file(File::default())
+            .add_file(File::default())
+            .set_compile_timeout(-2)
+            .set_run_memory_limit(-100);
+        executor.files[1].encoding = "utf16".to_string();
+
+        let problems = executor.validate_schema().unwrap_err();
+
+        assert_eq!(
+            problems,
+            vec![
+                "language must not be empty".to_string(),
+                "compile_timeout must be non negative or -1, got -2".to_string(),
+                "run_memory_limit must be non negative or -1, got -100".to_string(),
+                "files[1] has invalid encoding 'utf16', must be one of: utf8, hex, base64"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_schema() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .set_version("1.50.0")
+            .add_file(File::default().set_name("main.rs"))
+            .set_stdin("42")
+            .add_arg("--fast");
+
+        let value = serde_json::to_value(&*executor.payload()).unwrap();
+        let object = value.as_object().unwrap();
+
+        for key in ["language", "version", "stdin"] {
+            assert!(object[key].is_string(), "{} is not a string", key);
+        }
+
+        for key in [
+            "compile_timeout",
+            "run_timeout",
+            "compile_memory_limit",
+            "run_memory_limit",
+        ] {
+            assert!(object[key].is_i64(), "{} is not an integer", key);
+        }
+
+        assert!(object["args"].as_array().unwrap()[0].is_string());
+
+        let file = object["files"][0].as_object().unwrap();
+        for key in ["name", "content", "encoding"] {
+            assert!(file[key].is_string(), "files[0].{} is not a string", key);
+        }
+    }
+
+    #[test]
+    fn test_disabled_timeouts() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .disable_compile_timeout()
+            .disable_run_timeout();
+
+        let json = serde_json::to_value(&*executor.payload()).unwrap();
+        assert_eq!(json["compile_timeout"], -1);
+        assert_eq!(json["run_timeout"], -1);
+        assert!(executor.validate_schema().is_ok());
+    }
+
+    #[test]
+    fn test_merge() {
+        let template = Executor::new()
+            .set_language("python")
+            .set_version("3.10.0")
+            .add_file(File::default().set_name("harness.py"))
+            .add_arg("--strict")
+            .set_run_timeout(5000)
+            .set_stdin("default input");
+
+        let user = Executor::new()
+            .add_file(File::default().set_name("solution.py"))
+            .add_arg("--fast")
+            .set_stdin("42")
+            .set_run_memory_limit(1_000_000);
+
+        let executor = template.merge(user);
+
+        assert_eq!(executor.language, "python".to_string());
+        assert_eq!(executor.version, "3.10.0".to_string());
+        assert_eq!(executor.files.len(), 2);
+        assert_eq!(executor.files[0].name, "harness.py".to_string());
+        assert_eq!(executor.files[1].name, "solution.py".to_string());
+        assert_eq!(executor.args, vec!["--strict", "--fast"]);
+        assert_eq!(executor.stdin, "42".to_string());
+        assert_eq!(executor.run_timeout, 5000);
+        assert_eq!(executor.run_memory_limit, 1_000_000);
+        assert_eq!(executor.compile_timeout, 10000);
+    }
+
+    #[test]
+    fn test_set_main_file() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"))
+            .add_file(File::default().set_name("main.py"));
+
+        assert!(executor.set_main_file("main.py"));
+
+        let names: Vec<&str> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["main.py", "a.py", "b.py"]);
+    }
+
+    #[test]
+    fn test_set_main_file_not_found() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"));
+
+        assert!(!executor.set_main_file("main.py"));
+
+        let names: Vec<&str> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["a.py", "b.py"]);
+    }
+
+    #[test]
+    fn test_reorder_files() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"))
+            .add_file(File::default().set_name("c.py"));
+
+        executor.reorder_files(&["c.py", "a.py"]);
+
+        let names: Vec<&str> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["c.py", "a.py", "b.py"]);
+    }
+
+    #[test]
+    fn test_reorder_files_keeps_unnamed_order() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"))
+            .add_file(File::default().set_name("c.py"));
+
+        executor.reorder_files(&["b.py", "missing.py"]);
+
+        let names: Vec<&str> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["b.py", "a.py", "c.py"]);
+    }
+
+    #[test]
+    fn test_infer_language() {
+        let executor = Executor::new()
+            .add_file(File::default().set_name("main.py"))
+            .add_file(File::default().set_name("util.rs"))
+            .infer_language();
+
+        assert_eq!(executor.language, "python".to_string());
+
+        let executor = Executor::new()
+            .set_language("python3")
+            .add_file(File::default().set_name("main.rs"))
+            .infer_language();
+
+        assert_eq!(executor.language, "python3".to_string());
+    }
+
+    #[test]
+    fn test_infer_language_unknown() {
+        let executor = Executor::new()
+            .add_file(File::default().set_name("notes.txt"))
+            .infer_language();
+
+        assert!(executor.language.is_empty());
+
+        let executor = Executor::new()
+            .add_file(File::default().set_name("Makefile"))
+            .infer_language();
+
+        assert!(executor.language.is_empty());
+        assert!(Executor::new().infer_language().language.is_empty());
+    }
+
+    #[test]
+    fn test_remove_arg() {
+        let mut executor = Executor::new().add_args(vec!["-v", "--fast", "-v"]);
+
+        assert!(executor.remove_arg("-v"));
+        assert_eq!(executor.args, vec!["--fast", "-v"]);
+    }
+
+    #[test]
+    fn test_remove_arg_missing() {
+        let mut executor = Executor::new().add_args(vec!["-v", "--fast"]);
+
+        assert!(!executor.remove_arg("--slow"));
+        assert_eq!(executor.args, vec!["-v", "--fast"]);
+
+        executor.clear_args();
+        assert_eq!(executor.arg_count(), 0);
+    }
+
+    #[test]
+    fn test_remove_file() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"));
+
+        assert!(executor.remove_file("a.py"));
+        assert!(!executor.remove_file("a.py"));
+        assert_eq!(executor.files[0].name, "b.py".to_string());
+    }
+
+    #[test]
+    fn test_without_file() {
+        let original = Executor::new()
+            .add_file(File::default().set_name("a.py"))
+            .add_file(File::default().set_name("b.py"))
+            .add_file(File::default().set_name("c.py"));
+
+        let executor = original.clone().without_file("b.py");
+        let names: Vec<&str> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["a.py", "c.py"]);
+        assert_eq!(original.file_count(), 3);
+
+        let unchanged = executor.clone().without_file("missing.py");
+        assert_eq!(unchanged.files, executor.files);
+    }
+
+    #[test]
+    fn test_file_by_name() {
+        let executor = Executor::new()
+            .add_file(File::default().set_name("a.py").set_content("import b"))
+            .add_file(File::default().set_name("b.py").set_content("x = 1"));
+
+        assert_eq!(
+            executor.file_by_name("b.py").unwrap().content,
+            "x = 1".to_string()
+        );
+        assert!(executor.file_by_name("c.py").is_none());
+    }
+
+    #[test]
+    fn test_file_by_name_mut() {
+        let mut executor = Executor::new()
+            .add_file(File::default().set_name("a.py").set_content("import b"))
+            .add_file(File::default().set_name("b.py").set_content("x = 1"));
+
+        executor.file_by_name_mut("b.py").unwrap().content = "x = 2".to_string();
+
+        assert_eq!(executor.files[1].content, "x = 2".to_string());
+        assert_eq!(executor.files[0].content, "import b".to_string());
+        assert!(executor.file_by_name_mut("c.py").is_none());
+    }
+
+    #[test]
+    fn test_total_lines() {
+        let executor = Executor::new()
+            .add_file(File::default().set_content("import util\n\nprint(util.x)\n"))
+            .add_file(File::default().set_content("x = 1\r\ny = 2"))
+            .add_file(File::default());
+
+        assert_eq!(executor.files[0].line_count(), 3);
+        assert_eq!(executor.files[1].line_count(), 2);
+        assert_eq!(executor.files[2].line_count(), 0);
+        assert_eq!(executor.total_lines(), 5);
+    }
+
+    #[test]
+    fn test_set_stdin_lines() {
+        let executor = Executor::new().set_stdin_lines(&["2", "hello", "", "world"]);
+        assert_eq!(executor.stdin, "2\nhello\n\nworld\n".to_string());
+
+        let executor = executor.set_stdin_lines(&[]);
+        assert!(executor.stdin.is_empty());
+    }
+
+    #[test]
+    fn test_set_timeouts_and_memory_limits() {
+        let executor = Executor::new()
+            .set_timeouts(5000, 1500)
+            .set_memory_limits(200_000_000, 100_000_000);
+
+        assert_eq!(executor.compile_timeout, 5000);
+        assert_eq!(executor.run_timeout, 1500);
+        assert_eq!(executor.compile_memory_limit, 200_000_000);
+        assert_eq!(executor.run_memory_limit, 100_000_000);
+    }
+
+    #[test]
+    fn test_set_stdin_from_reader() {
+        let reader = Cursor::new("Hello\nworld".as_bytes().to_vec());
+        let executor = Executor::new().set_stdin_from_reader(reader).unwrap();
+
+        assert_eq!(executor.stdin, "Hello\nworld".to_string());
+    }
+
+    #[test]
+    fn test_set_stdin_from_reader_invalid_utf8() {
+        let reader = Cursor::new(vec![0xff, 0xfe]);
+        let result = Executor::new().set_stdin_from_reader(reader);
+
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let executor = Executor::new()
+            .set_language("rust")
+            .add_file(
+                File::default()
+                    .set_name("main.rs")
+                    .set_content("fn main() {}"),
+            )
+            .add_arg("--release");
+        let same = Executor::new()
+            .set_language("rust")
+            .add_file(
+                File::default()
+                    .set_name("main.rs")
+                    .set_content("fn main() {}"),
+            )
+            .add_arg("--release");
+
+        assert_eq!(executor.content_hash(), same.content_hash());
+        assert_ne!(
+            executor.content_hash(),
+            same.clone().set_version("1.50.0").content_hash()
+        );
+        assert_ne!(
+            executor.content_hash(),
+            same.clone().set_run_timeout(1000).content_hash()
+        );
+        assert_ne!(executor.content_hash(), same.add_arg("-v").content_hash());
+    }
+
+    #[test]
+    fn test_content_hash_field_boundaries() {
+        let a = Executor::new().set_stdin("ab").add_arg("c");
+        let b = Executor::new().set_stdin("a").add_arg("bc");
+
+        assert_ne!(a.content_hash(), b.content_hash());
+    }
+
+    #[test]
+    fn test_use_spec() {
+        let spec: RuntimeSpec = "rust@1.50.0".parse().unwrap();
+        let executor = Executor::new().set_stdin("42").use_spec(&spec);
+
+        assert_eq!(executor.language, "rust".to_string());
+        assert_eq!(executor.version, "1.50.0".to_string());
+        assert_eq!(executor.stdin, "42".to_string());
+    }
+
+    #[test]
+    fn test_diagnostics_valid() {
+        let executor = Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_name("main.py").set_content("print(42)"));
+
+        assert!(executor.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics_accumulate() {
+        let executor = Executor::new()
+            .set_language(" ")
+            .set_version("")
+            .add_file(File::default().set_name("main.py").set_encoding("utf16"))
+            .add_file(File::default().set_name("main.py").set_content("print(42)"))
+            .add_file(File::default().set_content("x = 1"))
+            .set_run_timeout(-5);
+
+        assert_eq!(
+            executor.diagnostics(),
+            vec![
+                "language is empty",
+                "version is empty, use '*' for the latest",
+                "file 'main.py' has invalid encoding 'utf16'",
+                "file 'main.py' is empty",
+                "file 'main.py' is provided more than once",
+                "run timeout is -5, but must be -1 or more",
+            ]
+        );
+
+        assert_eq!(
+            Executor::new().diagnostics(),
+            vec!["language is empty", "no files provided"]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let executor = Executor::new()
+            .set_language("python")
+            .set_version("3.10.0")
+            .add_files(vec![File::default(), File::default()])
+            .set_stdin("42");
+
+        let summary = format!("{}", executor);
+        assert!(summary.contains("python 3.10.0"));
+        assert!(summary.contains("files: 2"));
+        assert!(summary.contains("stdin: yes"));
+        assert!(summary.contains("args: no"));
     }
 }
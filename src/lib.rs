@@ -6,7 +6,8 @@
 //!
 //! ## Getting started
 //!
-//! Check out the [`Client`] and [`Executor`] documentation.
+//! Check out the [`Client`] and [`Executor`] documentation, or import
+//! the commonly used types with `use piston_rs::prelude::*`.
 //!
 //! ##### Make requests to Piston
 //!
@@ -41,20 +42,95 @@
 //! }
 //! # }
 //! ```
+//!
+//! ## Features
+//!
+//! - `rustls-tls` (*default*) - Uses [rustls](https://docs.rs/rustls)
+//!   for TLS, so OpenSSL is never required. Without it, only plain
+//!   http Piston instances can be used.
+//! - `compression` - Allows gzip compressing execute request bodies
+//!   with `Client::with_request_compression`, using the
+//!   [`flate2`](https://docs.rs/flate2) crate.
+//! - `logging` - Logs requests sent to, and responses received from
+//!   Piston at the debug level using the [`log`](https://docs.rs/log)
+//!   crate. The `Authorization` header is never logged.
+//! - `semver` - Compares runtime versions as semver using the
+//!   [`semver`](https://docs.rs/semver) crate, rather than lexically.
+//! - `tracing` - Wraps requests to Piston in
+//!   [`tracing`](https://docs.rs/tracing) spans, recording the
+//!   endpoint, status, and duration. Executions also record the
+//!   language and version. Headers are never recorded.
 
 // RIP shrimpie, gone but not forgotten.
 
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Logs a debug message, if the `logging` feature is enabled.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "logging")]
+        log::debug!($($arg)*);
+    };
+}
+
+/// Awaits a request future within a span recording the given fields,
+/// and the duration of the request, if the `tracing` feature is
+/// enabled.
+macro_rules! traced {
+    ($name:literal, $future:expr $(, $field:ident = $value:expr)* $(,)?) => {{
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::info_span!(
+                $name,
+                $($field = %$value,)*
+                status = tracing::field::Empty,
+                duration_ms = tracing::field::Empty,
+            );
+
+            let start = std::time::Instant::now();
+            let output = tracing::Instrument::instrument($future, span.clone()).await;
+            span.record("duration_ms", start.elapsed().as_millis() as u64);
+            output
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            $future.await
+        }
+    }};
+}
+
+/// Records the status returned by Piston on the current span, if the
+/// `tracing` feature is enabled.
+macro_rules! record_status {
+    ($status:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("status", $status.as_u16());
+    };
+}
 
 mod client;
+mod error;
 mod executor;
+#[cfg(test)]
+mod mock;
+pub mod prelude;
 
+pub use client::BatchSummary;
 pub use client::Client;
+pub use client::ClientBuilder;
+pub use error::PistonError;
 pub use executor::ExecResponse;
 pub use executor::ExecResult;
 pub use executor::Executor;
+pub use executor::ExecutorBuilder;
+pub use executor::Stage;
 
 /// A runtime available to be used by Piston.
 ///
@@ -63,7 +139,10 @@ pub use executor::Executor;
 /// Runtimes are not meant to be created manually. Instead, they should
 /// be fetched from Piston using [`Client::fetch_runtimes`] and stored,
 /// if you have a need for the information.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Fields this crate does not know about are ignored, so newer versions
+/// of Piston can add fields without breaking deserialization.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Runtime {
     /// The language.
     pub language: String,
@@ -73,6 +152,412 @@ pub struct Runtime {
     pub aliases: Vec<String>,
 }
 
+/// Languages Piston is known to compile before running.
+const COMPILED_LANGUAGES: &[&str] = &[
+    "c",
+    "c++",
+    "cobol",
+    "crystal",
+    "csharp",
+    "d",
+    "fortran",
+    "go",
+    "haskell",
+    "kotlin",
+    "nasm",
+    "nasm64",
+    "nim",
+    "ocaml",
+    "pascal",
+    "rust",
+    "swift",
+    "typescript",
+    "zig",
+];
+
+impl Runtime {
+    /// Whether or not this runtime supports the given language, either
+    /// by name or by one of its aliases.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the language or an alias matches.
+    ///
+    /// # Example
+    /// ```
+    /// let runtime = piston_rs::Runtime {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     aliases: vec!["py".to_string(), "python3".to_string()],
+    /// };
+    ///
+    /// assert!(runtime.supports("python"));
+    /// assert!(runtime.supports("py"));
+    /// assert!(!runtime.supports("rust"));
+    /// ```
+    pub fn supports(&self, language: &str) -> bool {
+        self.language == language || self.aliases.iter().any(|a| a == language)
+    }
+
+    /// Whether or not Piston is expected to compile code for this
+    /// runtime, and return a compile stage in the response.
+    ///
+    /// ##### Note
+    ///
+    /// Piston does not report this, so this is a heuristic based on a
+    /// list of languages that are known to be compiled.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the language is known to be compiled.
+    ///
+    /// # Example
+    /// ```
+    /// let runtime = piston_rs::Runtime {
+    ///     language: "rust".to_string(),
+    ///     version: "1.50.0".to_string(),
+    ///     aliases: vec!["rs".to_string()],
+    /// };
+    ///
+    /// assert!(runtime.is_compiled());
+    /// ```
+    pub fn is_compiled(&self) -> bool {
+        COMPILED_LANGUAGES.contains(&self.language.as_str())
+    }
+
+    /// Finds the runtime with the highest version for a language.
+    ///
+    /// Versions are compared as semver when the `semver` feature is
+    /// enabled and both versions are valid semver. Otherwise they are
+    /// compared lexically.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    /// - `runtimes` - The runtimes to search.
+    ///
+    /// # Returns
+    /// - [`Option<&Runtime>`] - The latest runtime, or [`None`] if
+    ///   no runtime matches the language.
+    ///
+    /// # Example
+    /// ```
+    /// let runtimes = vec![
+    ///     piston_rs::Runtime {
+    ///         language: "python".to_string(),
+    ///         version: "2.7.18".to_string(),
+    ///         aliases: vec!["py".to_string()],
+    ///     },
+    ///     piston_rs::Runtime {
+    ///         language: "python".to_string(),
+    ///         version: "3.10.0".to_string(),
+    ///         aliases: vec!["py".to_string()],
+    ///     },
+    /// ];
+    ///
+    /// let latest = piston_rs::Runtime::latest("py", &runtimes).unwrap();
+    /// assert_eq!(latest.version, "3.10.0".to_string());
+    /// assert!(piston_rs::Runtime::latest("rust", &runtimes).is_none());
+    /// ```
+    pub fn latest<'a>(language: &str, runtimes: &'a [Runtime]) -> Option<&'a Runtime> {
+        runtimes
+            .iter()
+            .filter(|r| r.supports(language))
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+}
+
+impl std::fmt::Display for Runtime {
+    /// Formats the runtime as `language@version`.
+    ///
+    /// # Example
+    /// ```
+    /// let runtime = piston_rs::Runtime {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     aliases: vec!["py".to_string()],
+    /// };
+    ///
+    /// assert_eq!(runtime.to_string(), "python@3.10.0".to_string());
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.language, self.version)
+    }
+}
+
+/// A language and version pair, such as one typed by a user, parsed
+/// from `language@version` syntax. The version defaults to `*` when
+/// omitted.
+///
+/// Use [`Executor::use_spec`] to apply it to an executor.
+///
+/// # Example
+/// ```
+/// let spec: piston_rs::RuntimeSpec = "python@3.10".parse().unwrap();
+///
+/// assert_eq!(spec.language, "python".to_string());
+/// assert_eq!(spec.version, "3.10".to_string());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RuntimeSpec {
+    /// The language, or one of its aliases.
+    pub language: String,
+    /// The version of the language.
+    pub version: String,
+}
+
+impl FromStr for RuntimeSpec {
+    type Err = PistonError;
+
+    /// Parses a spec from `language@version`, or a bare `language`.
+    ///
+    /// # Example
+    /// ```
+    /// let spec: piston_rs::RuntimeSpec = "rust".parse().unwrap();
+    /// assert_eq!(spec.version, "*".to_string());
+    ///
+    /// assert!("rust@".parse::<piston_rs::RuntimeSpec>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PistonError::InvalidExecutor(format!("invalid runtime '{}'", s));
+        let (language, version) = match s.trim().split_once('@') {
+            Some((language, version)) => (language, version),
+            None => (s.trim(), "*"),
+        };
+
+        let valid = |part: &str| !part.is_empty() && !part.contains(['@', ' ']);
+        if !valid(language) || !valid(version) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            language: language.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for RuntimeSpec {
+    /// Formats the spec as `language@version`.
+    ///
+    /// # Example
+    /// ```
+    /// let spec: piston_rs::RuntimeSpec = "rust".parse().unwrap();
+    ///
+    /// assert_eq!(spec.to_string(), "rust@*".to_string());
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.language, self.version)
+    }
+}
+
+/// Compares two versions, as semver if the `semver` feature is enabled
+/// and both are valid, falling back to lexical comparison.
+///
+/// # Arguments
+/// - `a` - The first version.
+/// - `b` - The second version.
+///
+/// # Returns
+/// - [`Ordering`] - The ordering of `a` relative to `b`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    #[cfg(feature = "semver")]
+    if let (Ok(a), Ok(b)) = (semver::Version::parse(a), semver::Version::parse(b)) {
+        return a.cmp(&b);
+    }
+
+    a.cmp(b)
+}
+
+/// Groups runtimes by their language, such as for a menu of languages
+/// and their versions. Within each language, runtimes keep the order
+/// they were given in.
+///
+/// # Arguments
+/// - `runtimes` - The runtimes to group.
+///
+/// # Returns
+/// - [`HashMap<String, Vec<Runtime>>`] - The runtimes for each
+///   language.
+///
+/// # Example
+/// ```
+/// let runtimes = vec![
+///     piston_rs::Runtime {
+///         language: "python".to_string(),
+///         version: "2.7.18".to_string(),
+///         aliases: vec![],
+///     },
+///     piston_rs::Runtime {
+///         language: "rust".to_string(),
+///         version: "1.50.0".to_string(),
+///         aliases: vec![],
+///     },
+/// ];
+///
+/// let groups = piston_rs::group_runtimes(&runtimes);
+/// assert_eq!(groups["python"].len(), 1);
+/// assert_eq!(groups["rust"][0].version, "1.50.0".to_string());
+/// ```
+pub fn group_runtimes(runtimes: &[Runtime]) -> HashMap<String, Vec<Runtime>> {
+    let mut groups: HashMap<String, Vec<Runtime>> = HashMap::new();
+
+    for runtime in runtimes {
+        groups
+            .entry(runtime.language.clone())
+            .or_default()
+            .push(runtime.clone());
+    }
+
+    groups
+}
+
+/// Convenience methods for runtimes fetched from Piston, such as with
+/// [`Client::fetch_runtimes`]. Implemented for slices, so they can also
+/// be called on a [`Vec<Runtime>`].
+///
+/// # Example
+/// ```
+/// use piston_rs::RuntimeExt;
+///
+/// let runtimes = vec![
+///     piston_rs::Runtime {
+///         language: "python".to_string(),
+///         version: "3.10.0".to_string(),
+///         aliases: vec!["py".to_string()],
+///     },
+///     piston_rs::Runtime {
+///         language: "rust".to_string(),
+///         version: "1.50.0".to_string(),
+///         aliases: vec!["rs".to_string()],
+///     },
+/// ];
+///
+/// assert_eq!(runtimes.by_language("py").count(), 1);
+/// assert_eq!(runtimes.languages(), vec!["python", "rust"]);
+/// assert_eq!(runtimes.versions_of("rs"), vec!["1.50.0"]);
+/// ```
+pub trait RuntimeExt {
+    /// The runtimes that support the given language, by name or alias.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`Box<dyn Iterator<Item = &Runtime>>`](Iterator) - The
+    ///   matching runtimes.
+    fn by_language<'a>(&'a self, language: &'a str) -> Box<dyn Iterator<Item = &'a Runtime> + 'a>;
+
+    /// The names of the languages, without duplicates across versions,
+    /// keeping the first occurrence of each.
+    ///
+    /// # Returns
+    /// - [`Vec<&str>`] - The unique language names.
+    fn languages(&self) -> Vec<&str>;
+
+    /// The versions of the given language, by name or alias.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`Vec<&str>`] - The versions, in their original order.
+    fn versions_of(&self, language: &str) -> Vec<&str>;
+}
+
+impl RuntimeExt for [Runtime] {
+    fn by_language<'a>(&'a self, language: &'a str) -> Box<dyn Iterator<Item = &'a Runtime> + 'a> {
+        Box::new(self.iter().filter(move |r| r.supports(language)))
+    }
+
+    fn languages(&self) -> Vec<&str> {
+        let mut languages: Vec<&str> = Vec::with_capacity(self.len());
+
+        for runtime in self {
+            if !languages.contains(&runtime.language.as_str()) {
+                languages.push(&runtime.language);
+            }
+        }
+
+        languages
+    }
+
+    fn versions_of(&self, language: &str) -> Vec<&str> {
+        self.iter()
+            .filter(|r| r.supports(language))
+            .map(|r| r.version.as_str())
+            .collect()
+    }
+}
+
+/// Common file extensions, and the Piston language they are written
+/// in.
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("asm", "nasm"),
+    ("bash", "bash"),
+    ("c", "c"),
+    ("cc", "c++"),
+    ("clj", "clojure"),
+    ("cpp", "c++"),
+    ("cr", "crystal"),
+    ("cs", "csharp"),
+    ("d", "d"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("f90", "fortran"),
+    ("go", "go"),
+    ("groovy", "groovy"),
+    ("hs", "haskell"),
+    ("java", "java"),
+    ("jl", "julia"),
+    ("js", "javascript"),
+    ("kt", "kotlin"),
+    ("lua", "lua"),
+    ("ml", "ocaml"),
+    ("nim", "nim"),
+    ("pas", "pascal"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("ps1", "powershell"),
+    ("py", "python"),
+    ("r", "rscript"),
+    ("rb", "ruby"),
+    ("rs", "rust"),
+    ("scala", "scala"),
+    ("sh", "bash"),
+    ("sql", "sqlite3"),
+    ("swift", "swift"),
+    ("ts", "typescript"),
+    ("zig", "zig"),
+];
+
+/// The Piston language a file with the given extension is likely
+/// written in.
+///
+/// # Arguments
+/// - `ext` - The extension, with or without a leading `.`. Case is
+///   ignored.
+///
+/// # Returns
+/// - [`Option<&str>`] - The Piston language name, or [`None`] if the
+///   extension is not recognized.
+///
+/// # Example
+/// ```
+/// assert_eq!(piston_rs::language_for_extension("py"), Some("python"));
+/// assert_eq!(piston_rs::language_for_extension(".RS"), Some("rust"));
+/// assert_eq!(piston_rs::language_for_extension("docx"), None);
+/// ```
+pub fn language_for_extension(ext: &str) -> Option<&'static str> {
+    let ext = ext.strip_prefix('.').unwrap_or(ext).to_lowercase();
+
+    EXTENSION_LANGUAGES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, language)| *language)
+}
+
 /// The result from attempting to load a [`File`].
 type LoadResult<T> = Result<T, LoadError>;
 
@@ -112,8 +597,13 @@ impl std::fmt::Display for LoadError {
     }
 }
 
+impl std::error::Error for LoadError {}
+
+/// The encodings Piston accepts for a [`File`].
+const ENCODINGS: &[&str] = &["utf8", "hex", "base64"];
+
 /// A file that contains source code to be executed.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct File {
     // The name of the file. Defaults to a new `String`.
     pub name: String,
@@ -154,7 +644,7 @@ impl File {
     /// - `name` - The name to use.
     /// - `content` - The content to use.
     /// - `encoding` - The encoding to use. Must be one of "utf8",
-    /// "hex", or "base64".
+    ///   "hex", or "base64".
     ///
     /// # Returns
     /// - [`File`] - The new File.
@@ -179,6 +669,32 @@ impl File {
         }
     }
 
+    /// Creates a new [`File`], base64 encoding the given plain text
+    /// content and setting the encoding to "base64".
+    ///
+    /// # Arguments
+    /// - `name` - The name to use.
+    /// - `content` - The plain text content to encode.
+    ///
+    /// # Returns
+    /// - [`File`] - The new File.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::base64("script.sh", "echo Hello, World!");
+    ///
+    /// assert_eq!(file.content, "ZWNobyBIZWxsbywgV29ybGQh".to_string());
+    /// assert_eq!(file.name, "script.sh".to_string());
+    /// assert_eq!(file.encoding, "base64".to_string());
+    /// ```
+    pub fn base64(name: &str, content: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            content: base64::engine::general_purpose::STANDARD.encode(content),
+            encoding: String::from("base64"),
+        }
+    }
+
     /// Creates a new [`File`] from an existing file on disk.
     ///
     /// # Arguments
@@ -216,6 +732,88 @@ impl File {
         })
     }
 
+    /// Creates a new [`File`] from an existing file on disk, like
+    /// [`File::load_from`], but fails without reading the file if it
+    /// is larger than the given size.
+    ///
+    /// # Arguments
+    /// - `path` - The path to the file.
+    /// - `max_bytes` - The maximum size of the file in bytes.
+    ///
+    /// # Returns
+    /// - [`File`] - The new File.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::load_from_limited("src/lib.rs", 1_000_000);
+    /// assert!(file.is_ok());
+    ///
+    /// let file = piston_rs::File::load_from_limited("src/lib.rs", 16);
+    /// assert!(file.is_err());
+    /// ```
+    pub fn load_from_limited(path: &str, max_bytes: u64) -> LoadResult<Self> {
+        let size = match fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => return Err(LoadError::new(&e.to_string())),
+        };
+
+        if size > max_bytes {
+            return Err(LoadError::new(&format!(
+                "File is {} bytes, exceeding the limit of {} bytes",
+                size, max_bytes
+            )));
+        }
+
+        File::load_from(path)
+    }
+
+    /// Creates a new [`File`] from an existing file on disk, choosing
+    /// the encoding from its contents. Valid UTF-8 is loaded as is with
+    /// the `utf8` encoding, and anything else is base64 encoded.
+    ///
+    /// # Arguments
+    /// - `path` - The path to the file.
+    ///
+    /// # Returns
+    /// - [`File`] - The new File.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::load_from_auto("src/lib.rs").unwrap();
+    ///
+    /// assert_eq!(file.name, "lib.rs".to_string());
+    /// assert_eq!(file.encoding, "utf8".to_string());
+    /// ```
+    pub fn load_from_auto(path: &str) -> LoadResult<Self> {
+        let path = PathBuf::from(path);
+
+        if !path.is_file() {
+            return Err(LoadError::new("File does not exist, or is a directory"));
+        }
+
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy().to_string(),
+            None => {
+                return Err(LoadError::new("Unable to parse file name"));
+            }
+        };
+
+        let bytes = fs::read(&path).map_err(|e| LoadError::new(&e.to_string()))?;
+        let (content, encoding) = match String::from_utf8(bytes) {
+            Ok(text) => (text, "utf8"),
+            Err(e) => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(e.into_bytes());
+                (encoded, "base64")
+            }
+        };
+
+        Ok(Self {
+            name,
+            content,
+            encoding: String::from(encoding),
+        })
+    }
+
     /// Loads the contents of the given file.
     ///
     /// # Arguments
@@ -306,7 +904,7 @@ impl File {
     ///
     /// # Arguments
     /// - `encoding` - The encoding to use. Must be one of "utf8",
-    /// "hex", or "base64".
+    ///   "hex", or "base64".
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
@@ -323,11 +921,51 @@ impl File {
         self.encoding = encoding.to_string();
         self
     }
+
+    /// The extension of the file's name, without the leading `.`.
+    ///
+    /// # Returns
+    /// - [`Option<&str>`] - The extension, or [`None`] if the name has
+    ///   no extension.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::default().set_name("main.rs");
+    /// assert_eq!(file.extension(), Some("rs"));
+    ///
+    /// let file = piston_rs::File::default().set_name("Makefile");
+    /// assert_eq!(file.extension(), None);
+    /// ```
+    pub fn extension(&self) -> Option<&str> {
+        Path::new(&self.name).extension().and_then(|e| e.to_str())
+    }
+
+    /// The number of lines in the file's content.
+    ///
+    /// # Returns
+    /// - [`usize`] - The number of lines.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::default()
+    ///     .set_content("fn main() {\n    println!(\"42\");\n}\n");
+    ///
+    /// assert_eq!(file.line_count(), 3);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        self.content.lines().count()
+    }
 }
 
 #[cfg(test)]
 mod test_file_private {
+    use std::collections::HashSet;
+
+    use base64::Engine;
+
+    use super::language_for_extension;
     use super::File;
+    use super::LoadError;
     use super::Runtime;
     use std::path::PathBuf;
 
@@ -339,6 +977,52 @@ mod test_file_private {
         assert!(contents.contains("mod test_file_private {"));
     }
 
+    #[test]
+    fn test_load_from_limited() {
+        let size = std::fs::metadata(file!()).unwrap().len();
+
+        let file = File::load_from_limited(file!(), size).unwrap();
+        assert!(file.content.contains("mod test_file_private {"));
+
+        let err = File::load_from_limited(file!(), size - 1).unwrap_err();
+        assert_eq!(
+            err.details,
+            format!(
+                "File is {} bytes, exceeding the limit of {} bytes",
+                size,
+                size - 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_load_from_auto_text() {
+        let file = File::load_from_auto(file!()).unwrap();
+
+        assert_eq!(file.name, "lib.rs".to_string());
+        assert_eq!(file.encoding, "utf8".to_string());
+        assert!(file.content.contains("mod test_file_private {"));
+    }
+
+    #[test]
+    fn test_load_from_auto_binary() {
+        let bytes = [0x00, 0x9f, 0x92, 0x96, 0xff];
+        let path = std::env::temp_dir().join(format!("piston_rs_{}.bin", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+
+        let file = File::load_from_auto(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let file = file.unwrap();
+        assert_eq!(file.encoding, "base64".to_string());
+        assert_eq!(
+            base64::engine::general_purpose::STANDARD
+                .decode(&file.content)
+                .unwrap(),
+            bytes
+        );
+    }
+
     #[test]
     fn test_load_contents_non_existent() {
         let path = PathBuf::from("/path/doesnt/exist");
@@ -375,4 +1059,259 @@ mod test_file_private {
         assert_eq!(rt.version, "9000".to_string());
         assert!(rt.aliases.is_empty());
     }
+
+    #[test]
+    fn test_file_hash_set() {
+        let file = File::default().set_name("main.rs");
+        let files: HashSet<File> = vec![file.clone(), file.clone(), File::default()]
+            .into_iter()
+            .collect();
+
+        assert_eq!(files.len(), 2);
+        assert!(files.contains(&file));
+    }
+
+    #[test]
+    fn test_file_base64_round_trip() {
+        let source = "fn main() { println!(\"héllo \\u{1F980}\"); }";
+        let file = File::base64("main.rs", source);
+
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&file.content)
+            .unwrap();
+
+        assert_eq!(String::from_utf8(decoded).unwrap(), source);
+        assert_eq!(file.encoding, "base64".to_string());
+    }
+
+    #[test]
+    fn test_file_extension() {
+        let file = File::default().set_name("src/main.py");
+        assert_eq!(file.extension(), Some("py"));
+
+        let file = File::default().set_name("archive.tar.gz");
+        assert_eq!(file.extension(), Some("gz"));
+
+        assert_eq!(File::default().extension(), None);
+        assert_eq!(File::default().set_name(".bashrc").extension(), None);
+    }
+
+    #[test]
+    fn test_language_for_extension() {
+        assert_eq!(language_for_extension("py"), Some("python"));
+        assert_eq!(language_for_extension("rs"), Some("rust"));
+        assert_eq!(language_for_extension(".js"), Some("javascript"));
+        assert_eq!(language_for_extension("CPP"), Some("c++"));
+        assert_eq!(language_for_extension("sh"), Some("bash"));
+        assert_eq!(language_for_extension("xyz"), None);
+        assert_eq!(language_for_extension(""), None);
+    }
+
+    #[test]
+    fn test_load_error_boxed() {
+        let err: Box<dyn std::error::Error> = Box::new(LoadError::new("missing file"));
+
+        assert_eq!(err.to_string(), "missing file".to_string());
+        assert!(err.source().is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_runtime_private {
+    use std::collections::HashSet;
+
+    use super::compare_versions;
+    use super::group_runtimes;
+    use super::PistonError;
+    use super::Runtime;
+    use super::RuntimeExt;
+    use super::RuntimeSpec;
+
+    #[test]
+    fn test_runtime_spec_with_version() {
+        let spec: RuntimeSpec = "rust@1.50.0".parse().unwrap();
+
+        assert_eq!(spec.language, "rust".to_string());
+        assert_eq!(spec.version, "1.50.0".to_string());
+        assert_eq!(spec.to_string(), "rust@1.50.0".to_string());
+    }
+
+    #[test]
+    fn test_runtime_spec_bare_language() {
+        let spec: RuntimeSpec = "python".parse().unwrap();
+
+        assert_eq!(spec.language, "python".to_string());
+        assert_eq!(spec.version, "*".to_string());
+    }
+
+    #[test]
+    fn test_runtime_spec_malformed() {
+        for input in ["", "@", "@1.50.0", "rust@", "rust@1@2", "ru st@1"] {
+            match input.parse::<RuntimeSpec>() {
+                Err(PistonError::InvalidExecutor(message)) => assert!(message.contains(input)),
+                other => panic!("Unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_runtime_display() {
+        let runtime = Runtime {
+            language: "rust".to_string(),
+            version: "1.50.0".to_string(),
+            aliases: vec![],
+        };
+
+        assert_eq!(runtime.to_string(), "rust@1.50.0".to_string());
+    }
+
+    fn sample_runtimes() -> Vec<Runtime> {
+        let runtime = |language: &str, version: &str, alias: &str| Runtime {
+            language: language.to_string(),
+            version: version.to_string(),
+            aliases: vec![alias.to_string()],
+        };
+
+        vec![
+            runtime("python", "2.7.18", "py"),
+            runtime("rust", "1.50.0", "rs"),
+            runtime("python", "3.10.0", "py"),
+        ]
+    }
+
+    #[test]
+    fn test_runtime_ext_by_language() {
+        let runtimes = sample_runtimes();
+
+        let versions: Vec<&str> = runtimes
+            .by_language("py")
+            .map(|r| r.version.as_str())
+            .collect();
+        assert_eq!(versions, vec!["2.7.18", "3.10.0"]);
+        assert_eq!(runtimes.by_language("cobol").count(), 0);
+    }
+
+    #[test]
+    fn test_runtime_ext_languages() {
+        assert_eq!(sample_runtimes().languages(), vec!["python", "rust"]);
+        assert!(Vec::<Runtime>::new().languages().is_empty());
+    }
+
+    #[test]
+    fn test_runtime_ext_versions_of() {
+        let runtimes = sample_runtimes();
+
+        assert_eq!(runtimes.versions_of("python"), vec!["2.7.18", "3.10.0"]);
+        assert_eq!(runtimes[..2].versions_of("rs"), vec!["1.50.0"]);
+        assert!(runtimes.versions_of("cobol").is_empty());
+    }
+
+    #[test]
+    fn test_compare_versions_lexical_fallback() {
+        assert_eq!(compare_versions("b", "a"), std::cmp::Ordering::Greater);
+        assert_eq!(compare_versions("1.x", "1.x"), std::cmp::Ordering::Equal);
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn test_runtime_latest_semver() {
+        let runtimes: Vec<Runtime> = ["1.2.0", "1.10.0", "1.9.0"]
+            .iter()
+            .map(|v| Runtime {
+                language: "rust".to_string(),
+                version: v.to_string(),
+                aliases: vec![],
+            })
+            .collect();
+
+        let latest = Runtime::latest("rust", &runtimes).unwrap();
+        assert_eq!(latest.version, "1.10.0".to_string());
+    }
+
+    #[test]
+    fn test_runtime_hash_set() {
+        let runtime = Runtime {
+            language: "rust".to_string(),
+            version: "1.50.0".to_string(),
+            aliases: vec!["rs".to_string()],
+        };
+
+        let runtimes: HashSet<Runtime> =
+            vec![runtime.clone(), runtime.clone()].into_iter().collect();
+
+        assert_eq!(runtimes.len(), 1);
+        assert!(runtimes.contains(&runtime));
+    }
+
+    #[test]
+    fn test_runtime_is_compiled() {
+        let runtime = |language: &str| Runtime {
+            language: language.to_string(),
+            version: "*".to_string(),
+            aliases: vec![],
+        };
+
+        assert!(runtime("c").is_compiled());
+        assert!(runtime("rust").is_compiled());
+        assert!(!runtime("python").is_compiled());
+        assert!(!runtime("javascript").is_compiled());
+    }
+
+    #[test]
+    fn test_group_runtimes() {
+        let runtime = |language: &str, version: &str| Runtime {
+            language: language.to_string(),
+            version: version.to_string(),
+            aliases: vec![],
+        };
+
+        let runtimes = vec![
+            runtime("python", "3.10.0"),
+            runtime("rust", "1.50.0"),
+            runtime("python", "2.7.18"),
+        ];
+
+        let groups = group_runtimes(&runtimes);
+        assert_eq!(groups.len(), 2);
+
+        let versions: Vec<&str> = groups["python"]
+            .iter()
+            .map(|r| r.version.as_str())
+            .collect();
+        assert_eq!(versions, vec!["3.10.0", "2.7.18"]);
+        assert_eq!(groups["rust"], vec![runtime("rust", "1.50.0")]);
+    }
+
+    #[test]
+    fn test_runtime_unknown_fields() {
+        let body = r#"[
+            {"language": "rust", "version": "1.50.0", "aliases": ["rs"], "runtime": "rustc"},
+            {"language": "python", "version": "3.10.0", "aliases": [], "platform": {"arch": "x86_64"}}
+        ]"#;
+
+        let runtimes: Vec<Runtime> = serde_json::from_str(body).unwrap();
+        assert_eq!(runtimes.len(), 2);
+        assert_eq!(runtimes[0].aliases, vec!["rs".to_string()]);
+        assert_eq!(runtimes[1].language, "python".to_string());
+    }
+}
+
+#[cfg(test)]
+mod test_send_sync_private {
+    use super::Client;
+    use super::ExecResponse;
+    use super::Executor;
+    use super::File;
+    use super::Runtime;
+
+    fn _assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        _assert_send_sync::<Client>();
+        _assert_send_sync::<Executor>();
+        _assert_send_sync::<ExecResponse>();
+        _assert_send_sync::<File>();
+        _assert_send_sync::<Runtime>();
+    }
 }
@@ -0,0 +1,189 @@
+//! A minimal http server used to mock Piston in tests.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A request received by the [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// The http method of the request.
+    pub method: String,
+    /// The path of the request.
+    pub path: String,
+    /// The headers of the request, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    /// The body of the request.
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    /// The value of the given header, if it was sent.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response the [`MockServer`] replies with.
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    /// The status code to reply with.
+    pub status: u16,
+    /// The extra headers to reply with.
+    pub headers: Vec<(String, String)>,
+    /// The body to reply with.
+    pub body: String,
+    /// How long to wait before replying.
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// A response with the given status and body.
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.to_string(),
+            delay: None,
+        }
+    }
+
+    /// Adds a header to the response.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Delays the response by the given duration.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// A http server replying to each request with the next queued
+/// response. The last response is repeated once the queue runs out.
+pub struct MockServer {
+    /// The base url of the server.
+    pub url: String,
+    /// The requests received so far.
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Starts a server on a random local port.
+    pub fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(responses));
+
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let received = Arc::clone(&received);
+                let responses = Arc::clone(&responses);
+                thread::spawn(move || Self::handle(stream, &received, &responses));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Reads a single request from the stream and replies to it.
+    fn handle(
+        stream: TcpStream,
+        received: &Mutex<Vec<MockRequest>>,
+        responses: &Mutex<Vec<MockResponse>>,
+    ) {
+        let mut reader = BufReader::new(stream);
+        let request = match Self::read_request(&mut reader) {
+            Some(r) => r,
+            None => return,
+        };
+
+        let response = {
+            let mut responses = responses.lock().unwrap();
+            received.lock().unwrap().push(request);
+
+            match responses.len() {
+                0 => MockResponse::new(404, ""),
+                1 => responses[0].clone(),
+                _ => responses.remove(0),
+            }
+        };
+
+        if let Some(delay) = response.delay {
+            thread::sleep(delay);
+        }
+
+        let mut stream = reader.into_inner();
+        let mut head = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+            response.status,
+            response.body.len()
+        );
+
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        head.push_str("\r\n");
+        let _ = stream.write_all(head.as_bytes());
+        let _ = stream.write_all(response.body.as_bytes());
+        let _ = stream.flush();
+    }
+
+    /// Parses the request line, headers, and body from the reader.
+    fn read_request(reader: &mut BufReader<TcpStream>) -> Option<MockRequest> {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+
+        let mut parts = line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let path = parts.next()?.to_string();
+
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+
+            let (name, value) = line.split_once(':')?;
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+
+        let length = headers
+            .iter()
+            .find(|(n, _)| n == "content-length")
+            .and_then(|(_, v)| v.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).ok()?;
+
+        Some(MockRequest {
+            method,
+            path,
+            headers,
+            body,
+        })
+    }
+}
@@ -0,0 +1,28 @@
+//! Commonly used types, for convenient glob importing.
+//!
+//! ```
+//! use piston_rs::prelude::*;
+//!
+//! let client = Client::new();
+//! let executor = Executor::new()
+//!     .set_language("rust")
+//!     .add_file(File::default().set_content("fn main() {}"));
+//!
+//! assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+//! assert_eq!(executor.language, "rust".to_string());
+//! ```
+
+pub use super::BatchSummary;
+pub use super::Client;
+pub use super::ClientBuilder;
+pub use super::ExecResponse;
+pub use super::ExecResult;
+pub use super::Executor;
+pub use super::ExecutorBuilder;
+pub use super::File;
+pub use super::LoadError;
+pub use super::PistonError;
+pub use super::Runtime;
+pub use super::RuntimeExt;
+pub use super::RuntimeSpec;
+pub use super::Stage;